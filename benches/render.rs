@@ -0,0 +1,212 @@
+//! Benchmarks for drawing a small dashboard, which is the kind of `Panel` that is redrawn at 60 Hz.
+//!
+//! `render_image` allocates a fresh frame for every draw, while `draw` paints straight into a
+//! pixel buffer that is reused, and `draw_damaged_*` only repaints what changed. The allocator
+//! counts the allocations, so that `frames_do_not_allocate` can check that drawing a frame is free
+//! of them.
+//!
+//! Run with `cargo bench`.
+
+#![feature(test)]
+
+extern crate test;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use fleck::Font;
+use stammer::elements::builder::ElementBuilder;
+use stammer::elements::{Alignment, Content, Element, Graph};
+use stammer::{Panel, PIXEL_SIZE};
+use test::Bencher;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations this thread has made so far.
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+struct Data {
+    frame: usize,
+    /// The names of the sensors, one label for each, built through a list binding.
+    sensors: Vec<String>,
+    load: Graph,
+    temperature: Graph,
+}
+
+impl Data {
+    fn tick(&mut self) {
+        self.frame += 1;
+        self.load.push((self.frame % 17) as f32);
+        self.temperature.push((self.frame % 5) as f32);
+    }
+}
+
+const GRAPH_WIDTH: u32 = 150;
+const GRAPH_HEIGHT: u32 = 16;
+
+fn dashboard() -> Panel<Data> {
+    fn graph(element: &mut Element<Data>, graph: &Graph) {
+        if let Content::Custom { buf, height } = &mut element.content {
+            graph.paint(buf, *height, &element.style);
+        }
+    }
+
+    fn frame_counter(element: &mut Element<Data>, data: &Data) {
+        if let Content::Text(text, _) = &mut element.content {
+            text.clear();
+            write!(text, "frame {}", data.frame).unwrap();
+        }
+    }
+
+    let font = Rc::new(Font::new(include_bytes!("../cream12.uf2")));
+    let label = |s: &str| {
+        Element::still(
+            Rc::clone(&font),
+            Content::Text(s.to_string(), Alignment::Left),
+        )
+        .with_padding_right(8)
+    };
+    let custom = || Content::Custom {
+        buf: vec![[0x00; 4]; (GRAPH_WIDTH * GRAPH_HEIGHT) as usize],
+        height: GRAPH_HEIGHT,
+    };
+    let row = |children| Element::still(Rc::clone(&font), Content::Row(children));
+    let elements = Element::still(
+        Rc::clone(&font),
+        Content::Stack(vec![
+            Element::dynamic(
+                frame_counter,
+                Rc::clone(&font),
+                Content::Text(String::new(), Alignment::Right),
+            )
+            .with_minwidth(200),
+            Element::row_builder(&font)
+                .with_binding(
+                    |data: &Data| data.sensors.len(),
+                    |data, idx| &data.sensors[idx],
+                    |data, idx, font| {
+                        let text = Content::Text(data.sensors[idx].clone(), Alignment::Left);
+                        Element::still(Rc::clone(font), text).with_padding_right(8)
+                    },
+                )
+                .build(),
+            row(vec![
+                label("load"),
+                Element::dynamic(
+                    |element, data| graph(element, &data.load),
+                    Rc::clone(&font),
+                    custom(),
+                ),
+            ]),
+            row(vec![
+                label("temperature"),
+                Element::dynamic(
+                    |element, data| graph(element, &data.temperature),
+                    Rc::clone(&font),
+                    custom(),
+                ),
+            ]),
+        ]),
+    );
+    let data = Data {
+        frame: 0,
+        sensors: vec!["cpu".to_string(), "gpu".to_string(), "disk".to_string()],
+        load: Graph::new(GRAPH_WIDTH as usize),
+        temperature: Graph::new(GRAPH_WIDTH as usize),
+    };
+    let mut panel = Panel::new(elements, [0xff; 4], [0x00, 0x00, 0x00, 0xff], data).unwrap();
+    panel.update().unwrap();
+    panel
+}
+
+fn pixels(panel: &Panel<Data>) -> Vec<u8> {
+    vec![0; panel.width as usize * panel.height as usize * PIXEL_SIZE]
+}
+
+#[bench]
+fn render_image(b: &mut Bencher) {
+    let panel = dashboard();
+    b.iter(|| panel.render().unwrap());
+}
+
+#[bench]
+fn draw(b: &mut Bencher) {
+    let panel = dashboard();
+    let mut pixels = pixels(&panel);
+    b.iter(|| panel.draw(&mut pixels).unwrap());
+}
+
+#[bench]
+fn draw_damaged_unchanged(b: &mut Bencher) {
+    let mut panel = dashboard();
+    let mut pixels = pixels(&panel);
+    b.iter(|| panel.draw_damaged(&mut pixels).map(<[_]>::len).unwrap());
+}
+
+#[bench]
+fn update_and_draw_damaged(b: &mut Bencher) {
+    let mut panel = dashboard();
+    let mut pixels = pixels(&panel);
+    b.iter(|| {
+        panel.data_mut().tick();
+        panel.update().unwrap();
+        panel.draw_damaged(&mut pixels).map(<[_]>::len).unwrap()
+    });
+}
+
+#[test]
+fn frames_do_not_allocate() {
+    let mut panel = dashboard();
+    let mut pixels = pixels(&panel);
+    // The first frames may still grow some buffers, such as the damage list and the texts.
+    for _ in 0..10 {
+        panel.data_mut().tick();
+        panel.update().unwrap();
+        panel.draw_damaged(&mut pixels).unwrap();
+    }
+
+    let before = allocations();
+    for _ in 0..60 {
+        panel.data_mut().tick();
+        panel.update().unwrap();
+        panel.draw_damaged(&mut pixels).unwrap();
+        panel.draw(&mut pixels).unwrap();
+    }
+    assert_eq!(allocations() - before, 0);
+
+    // Rendering into an owned image allocates that image, of course.
+    let before = allocations();
+    panel.render().unwrap();
+    assert!(allocations() > before);
+}
@@ -0,0 +1,45 @@
+use std::rc::Rc;
+
+use fleck::Font;
+use stammer::elements::builder::ElementBuilder;
+use stammer::elements::{Alignment, Element, SizingStrategy};
+use stammer::Panel;
+
+fn setup_elements(font: Rc<Font>) -> Element<()> {
+    Element::stack_builder(&font)
+        .add_child(
+            Element::text("headless", &font)
+                .with_alignment(Alignment::Center)
+                .build()
+                .with_strategy(SizingStrategy::Chonker)
+                .with_maxwidth(200)
+                .with_background([0xaa, 0xaa, 0xff, 0xff])
+                .with_padding_bottom(8),
+        )
+        .add_child(
+            Element::paragraph(include_str!("lorem.txt"), &font)
+                .build()
+                .with_maxwidth(200),
+        )
+        .build()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let out = args.next().unwrap_or("headless".to_string());
+
+    let font = Font::new(include_bytes!("../cream12.uf2"));
+    let elements = setup_elements(Rc::new(font));
+    let panel = Panel::new(
+        elements,
+        [0x00, 0x00, 0x00, 0xff],
+        [0xff, 0xff, 0xff, 0xff],
+        (),
+    )?;
+
+    let image = panel.render()?;
+    eprintln!("INFO:  Rendered a {}x{} frame.", image.width, image.height);
+    image.save_png(format!("{out}.png"))?;
+    image.save_ppm(format!("{out}.ppm"))?;
+    Ok(())
+}
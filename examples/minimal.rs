@@ -151,7 +151,11 @@ fn main() -> Result<(), pixels::Error> {
         [0x00, 0x00, 0x00, 0xff],
         [0xff, 0xff, 0xff, 0xff],
         data,
-    );
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("ERROR: Failed to lay out the elements: {err}");
+        std::process::exit(1);
+    });
 
     let (width, height) = (state.width, state.height);
     // TODO: This is _SUCH_ a papercut or even pitfall, as I just saw.
@@ -177,29 +181,30 @@ fn main() -> Result<(), pixels::Error> {
     event_loop.run(move |event, _, control_flow| {
         control_flow.set_poll();
 
-        match event {
-            // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
-            //     window.request_redraw()
-            // }
-            Event::RedrawRequested(_) => {
-                // Clear the screen before drawing.
-                pixels
-                    .frame_mut()
-                    .array_chunks_mut()
-                    .for_each(|px| *px = state.background);
-
-                // Update the state, then draw.
-                state.update();
-                state.draw(&mut pixels.frame_mut());
-
-                // Try to render.
-                if let Err(err) = pixels.render() {
-                    eprintln!("ERROR: {err}");
-                    control_flow.set_exit();
-                    return;
-                }
+        // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
+        //     window.request_redraw()
+        // }
+        if let Event::RedrawRequested(_) = event {
+            // Clear the screen before drawing.
+            pixels
+                .frame_mut()
+                .array_chunks_mut()
+                .for_each(|px| *px = state.background);
+
+            // Update the state, then draw.
+            if let Err(err) = state.update() {
+                eprintln!("ERROR: {err}");
+            }
+            if let Err(err) = state.draw(pixels.frame_mut()) {
+                eprintln!("ERROR: {err}");
+            }
+
+            // Try to render.
+            if let Err(err) = pixels.render() {
+                eprintln!("ERROR: {err}");
+                control_flow.set_exit();
+                return;
             }
-            _ => (),
         }
 
         if input.update(&event) {
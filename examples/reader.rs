@@ -6,10 +6,11 @@ use fleck::Font;
 use pixels::wgpu::BlendState;
 use pixels::{PixelsBuilder, SurfaceTexture};
 use stammer::elements::builder::ElementBuilder;
-use stammer::elements::{Alignment, Element, WrappedText, Content, SizingStrategy};
+use stammer::elements::{Alignment, Content, Element, FocusStyle, Scrollbar};
+use stammer::event::{Button, Key, Modifiers};
 use stammer::Panel;
 use winit::dpi::{LogicalSize, PhysicalSize};
-use winit::event::{Event, VirtualKeyCode};
+use winit::event::{Event as WinitEvent, VirtualKeyCode};
 use winit::event_loop::EventLoop;
 use winit::window::{Window, WindowBuilder};
 use winit_input_helper::{TextChar, WinitInputHelper};
@@ -17,7 +18,7 @@ use winit_input_helper::{TextChar, WinitInputHelper};
 const WINDOW_NAME: &str = env!("CARGO_BIN_NAME");
 
 const LOREM: &str = include_str!("lorem.txt");
-const SCROLL_STEP: usize = 8;
+const SCROLL_STEP: u32 = 8;
 
 fn setup_window(min_size: PhysicalSize<u32>, event_loop: &EventLoop<()>) -> Window {
     let builder = WindowBuilder::new()
@@ -42,40 +43,60 @@ fn load_font(path: &str) -> std::io::Result<Font> {
 }
 
 fn setup_elements(font: Rc<Font>) -> Element<Data> {
-    fn display_address(element: &mut Element<Data>, data: &Data) {
-        // TODO: This whole practice is a mess and is horrible and oh no.
-        let Content::Text(text, _) = &mut element.content else {
-            unreachable!()
+    fn edit_address(_: &mut Element<Data>, data: &mut Data, event: &stammer::Event) -> bool {
+        // The text input takes care of the editing itself, we only need to catch Enter.
+        let stammer::Event::KeyPress {
+            key: Key::Enter, ..
+        } = event
+        else {
+            return false;
         };
-        text.clear();
-        text.push_str(data.address.as_str())
+        data.address.clear();
+        eprintln!("Please pretend some other site's text is loading.");
+        true
     }
 
-    fn display_text(element: &mut Element<Data>, data: &Data) {
-        // TODO: This whole practice is a mess and is horrible and oh no.
-        let Content::Paragraph(text, _) = &mut element.content else {
-            unreachable!()
-        };
-
+    fn update_size(element: &mut Element<Data>, data: &Data) {
+        // The text itself is bound to the data, so only the size is left to us. The scroll is kept
+        // by the element itself, since both the keys and the scrollbar move it, and it is brought
+        // back within range when the text becomes shorter.
         element.size.maxwidth = Some(data.width);
         element.size.minwidth = Some(data.width);
-
-        *text = WrappedText::new(data.text.clone(), data.width, &element.style.font)
-    }
-
-    fn update_scroll(element: &mut Element<Data>, data: &Data) {
-        element.scroll = Some(data.scroll_pos as u32);
-        element.size.maxheight = data.height.checked_sub(2 * element.style.font.height() as u32); 
-        element.size.minheight = data.height.checked_sub(2 * element.style.font.height() as u32);
+        let height = data
+            .height
+            .checked_sub(2 * element.style.font.height() as u32);
+        element.size.maxheight = height;
+        element.size.minheight = height;
     }
 
-    fn display_mode(element: &mut Element<Data>, data: &Data) {
-        // TODO: This whole practice is a mess and is horrible and oh no.
-        let Content::Text(text, _) = &mut element.content else {
-            unreachable!()
+    fn scroll_text(element: &mut Element<Data>, _: &mut Data, event: &stammer::Event) -> bool {
+        let stammer::Event::KeyPress { key, .. } = event else {
+            return false;
         };
-        text.clear();
-        text.push_str(data.mode.to_string().as_str())
+        let Some(text) = element.get_mut("text") else {
+            return false;
+        };
+        let page = text.fill_size().height;
+        let end = text.max_scroll().height;
+        let pos = text.scroll.unwrap_or_default();
+        let scroll = match key {
+            Key::Up => pos.saturating_sub(SCROLL_STEP),
+            Key::Down => pos + SCROLL_STEP,
+            Key::PageUp => pos.saturating_sub(page),
+            Key::PageDown => pos + page,
+            Key::Home => 0,
+            // Scroll to the last line, as far as the text allows.
+            Key::End => {
+                let Content::Paragraph(wrapped, _) = &text.content else {
+                    unreachable!()
+                };
+                let last = wrapped.lines_count().saturating_sub(1);
+                text.line_offset(last).unwrap_or_default()
+            }
+            _ => return false,
+        };
+        text.scroll = Some(scroll.min(end));
+        true
     }
 
     fn resize_height(element: &mut Element<Data>, data: &Data) {
@@ -85,43 +106,44 @@ fn setup_elements(font: Rc<Font>) -> Element<Data> {
 
     Element::stack_builder(&font)
         .with_update(resize_height)
+        .with_handler(scroll_text)
         .add_child(
-            Element::text("---", &font)
-                .with_update(display_address)
-                .with_alignment(Alignment::Left)
+            Element::text_input("", &font)
+                .with_binding(|data: &Data| &data.address, |data| &mut data.address)
+                .with_handler(edit_address)
                 .build()
-            )
+                .with_id("address")
+                .with_focus_style(FocusStyle::Swap),
+        )
         .add_child(
-            Element::stack_builder(&font)
-            .with_update(update_scroll)
-            .add_child(
-                Element::empty_paragraph(&font)
-                    .with_update(display_text)
-                    .with_alignment(Alignment::Left)
-                    .build()
-                    .with_strategy(SizingStrategy::Chonker)
-                    .with_maxheight(700)
-                    .with_background([0xff, 0x00, 0x00, 0xff])
-            )
-            .build()
-            .with_scroll(0)
-            .with_minwidth(600)
-            .with_maxheight(400)
-            .with_minheight(400)
-            .with_background([0x00, 0xff, 0x00, 0xff])
+            Element::empty_paragraph(&font)
+                .with_binding(|data: &Data| &data.text)
+                .with_update(update_size)
+                .with_alignment(Alignment::Left)
+                .build()
+                .with_id("text")
+                .with_scroll(0)
+                .with_scrollbar(Scrollbar::new(4))
+                .with_background([0xff, 0x00, 0x00, 0xff]),
         )
         .add_child(
             Element::text("---", &font)
-            .with_update(display_mode)
-            .with_alignment(Alignment::Left)
-            .build()
+                .with_binding(|data: &Data| data.mode.to_string())
+                .with_alignment(Alignment::Left)
+                .build(),
         )
         .build()
 }
 
+fn key_press(key: Key) -> stammer::Event {
+    stammer::Event::KeyPress {
+        key,
+        modifiers: Modifiers::default(),
+    }
+}
+
 struct Data {
     text: String,
-    scroll_pos: usize,
     address: String,
     mode: Mode,
     width: u32,
@@ -135,12 +157,12 @@ enum Mode {
     Link,
 }
 
-impl ToString for Mode {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Mode::Normal => "normal".to_string(),
-            Mode::Insert => "insert".to_string(),
-            Mode::Link => "link".to_string(),
+            Mode::Normal => write!(f, "normal"),
+            Mode::Insert => write!(f, "insert"),
+            Mode::Link => write!(f, "link"),
         }
     }
 }
@@ -170,7 +192,6 @@ fn main() -> Result<(), pixels::Error> {
     let elements = setup_elements(font);
     let data = Data {
         text: [LOREM; 8].concat().to_string(),
-        scroll_pos: 0,
         address: "gemini://example.com/".to_string(),
         mode: Mode::Normal,
         width: 0,
@@ -181,7 +202,11 @@ fn main() -> Result<(), pixels::Error> {
         [0x00, 0x00, 0x00, 0xff],
         [0xff, 0xff, 0xff, 0xff],
         data,
-    );
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("ERROR: Failed to lay out the elements: {err}");
+        std::process::exit(1);
+    });
 
     let (width, height) = (state.width, state.height);
     let size = PhysicalSize::new(width * scale_factor, height * scale_factor);
@@ -207,87 +232,132 @@ fn main() -> Result<(), pixels::Error> {
     event_loop.run(move |event, _, control_flow| {
         control_flow.set_poll();
 
-        match event {
-            // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
-            //     window.request_redraw()
-            // }
-            Event::RedrawRequested(_) => {
-                // Clear the screen before drawing.
-                pixels
-                    .frame_mut()
-                    .array_chunks_mut()
-                    .for_each(|px| *px = state.background);
-
-                eprintln!("INFO: Redrawing...");
-                // Update the state, then draw.
-                state.update();
-                state.draw(&mut pixels.frame_mut());
-
-                // Try to render.
-                if let Err(err) = pixels.render() {
-                    eprintln!("ERROR: {err}");
-                    control_flow.set_exit();
-                    return;
-                }
+        // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
+        //     window.request_redraw()
+        // }
+        if let WinitEvent::RedrawRequested(_) = event {
+            // Clear the screen before drawing.
+            pixels
+                .frame_mut()
+                .array_chunks_mut()
+                .for_each(|px| *px = state.background);
+
+            eprintln!("INFO: Redrawing...");
+            // Update the state, then draw.
+            if let Err(err) = state.update() {
+                eprintln!("ERROR: {err}");
+            }
+            if let Err(err) = state.draw(pixels.frame_mut()) {
+                eprintln!("ERROR: {err}");
+            }
+
+            // Try to render.
+            if let Err(err) = pixels.render() {
+                eprintln!("ERROR: {err}");
+                control_flow.set_exit();
+                return;
             }
-            _ => (),
         }
 
         if input.update(&event) {
-            // Scroll around.
-            if input.key_pressed(VirtualKeyCode::Up) | input.key_pressed(VirtualKeyCode::K) {
-                let pos = &mut state.data_mut().scroll_pos;
-                *pos = pos.saturating_sub(SCROLL_STEP);
-                window.request_redraw();
+            // Scroll around, unless the keys are meant for the address bar.
+            let typing = state.focused().is_some();
+            for (codes, key) in [
+                ([VirtualKeyCode::Up, VirtualKeyCode::K], Key::Up),
+                ([VirtualKeyCode::Down, VirtualKeyCode::J], Key::Down),
+                ([VirtualKeyCode::PageUp, VirtualKeyCode::B], Key::PageUp),
+                (
+                    [VirtualKeyCode::PageDown, VirtualKeyCode::Space],
+                    Key::PageDown,
+                ),
+                ([VirtualKeyCode::Home, VirtualKeyCode::G], Key::Home),
+                ([VirtualKeyCode::End, VirtualKeyCode::E], Key::End),
+            ] {
+                let pressed = codes.iter().any(|&code| input.key_pressed(code));
+                if !typing && pressed && state.handle_event(key_press(key)) {
+                    window.request_redraw();
+                }
             }
 
-            if input.key_pressed(VirtualKeyCode::Down) | input.key_pressed(VirtualKeyCode::J) {
-                state.data_mut().scroll_pos += SCROLL_STEP;
-                window.request_redraw();
+            // Pass the pointer on, so that the scrollbar can be dragged.
+            if let Some((x, y)) = input.mouse() {
+                let (x, y) = (x as u32 / scale_factor, y as u32 / scale_factor);
+                let button = Button::Left;
+                let moved = input.mouse_diff() != (0.0, 0.0);
+                let events = [
+                    moved.then_some(stammer::Event::PointerMove { x, y }),
+                    input
+                        .mouse_pressed(0)
+                        .then_some(stammer::Event::PointerPress { x, y, button }),
+                    input
+                        .mouse_released(0)
+                        .then_some(stammer::Event::PointerRelease { x, y, button }),
+                ];
+                for event in events.into_iter().flatten() {
+                    if state.handle_event(event) {
+                        window.request_redraw();
+                    }
+                }
             }
 
             // Set mode.
-            {
-                let data = state.data_mut();
-                let mode = &mut data.mode;
-
-                match mode {
-                    Mode::Normal => {
-                        if input.key_pressed(VirtualKeyCode::I) {
-                            *mode = Mode::Insert;
-                            window.request_redraw();
+            match state.data_mut().mode {
+                Mode::Normal => {
+                    if input.key_pressed(VirtualKeyCode::I) {
+                        if let Some(path) = state.elements.path_of("address") {
+                            state.focus(&path);
                         }
-                        if input.key_pressed(VirtualKeyCode::F) {
-                            eprintln!(
-                                "TODO: The implementation of `Mode::Link` has been \
-                                left as an exercise to cute ppl. <3"
-                            );
-                            *mode = Mode::Link;
+                        state.data_mut().mode = Mode::Insert;
+                        window.request_redraw();
+                    }
+                    if input.key_pressed(VirtualKeyCode::F) {
+                        eprintln!(
+                            "TODO: The implementation of `Mode::Link` has been \
+                            left as an exercise to cute ppl. <3"
+                        );
+                        state.data_mut().mode = Mode::Link;
+                        window.request_redraw();
+                    }
+                }
+                Mode::Insert => {
+                    // Typed text goes to whichever element holds the focus.
+                    for ch in input.text() {
+                        let event = match ch {
+                            TextChar::Char('\n' | '\r') => key_press(Key::Enter),
+                            TextChar::Char(ch) => stammer::Event::Text(ch),
+                            TextChar::Back => key_press(Key::Backspace),
+                        };
+                        if state.handle_event(event) {
                             window.request_redraw();
                         }
                     }
-                    Mode::Insert => {
-                        for ch in input.text() {
-                            match ch {
-                                TextChar::Char('\n') => {
-                                    data.address.clear();
-                                    eprintln!("Please pretend some other site's text is loading.")
-                                }
-                                TextChar::Char(ch) => data.address.push(ch),
-                                TextChar::Back => {
-                                    let _ = data.address.pop();
-                                }
-                            }
+                    // As well as the keys for moving the cursor around.
+                    for (code, key) in [
+                        (VirtualKeyCode::Left, Key::Left),
+                        (VirtualKeyCode::Right, Key::Right),
+                        (VirtualKeyCode::Home, Key::Home),
+                        (VirtualKeyCode::End, Key::End),
+                        (VirtualKeyCode::Delete, Key::Delete),
+                    ] {
+                        let event = stammer::Event::KeyPress {
+                            key,
+                            modifiers: Modifiers {
+                                shift: input.held_shift(),
+                                ..Default::default()
+                            },
+                        };
+                        if input.key_pressed(code) && state.handle_event(event) {
                             window.request_redraw();
                         }
                     }
-                    Mode::Link => { /* TODO */ }
                 }
+                Mode::Link => { /* TODO */ }
+            }
 
-                if input.key_pressed(VirtualKeyCode::Escape) {
-                    *mode = Mode::Normal;
-                    window.request_redraw();
-                }
+            if input.key_pressed(VirtualKeyCode::Escape) {
+                state.blur();
+                state.data_mut().mode = Mode::Normal;
+                window.request_redraw();
             }
 
             // Close events.
@@ -1,16 +1,15 @@
-#![feature(array_chunks)]
-
 use std::collections::VecDeque;
 use std::rc::Rc;
 
 use fleck::Font;
 use pixels::wgpu::BlendState;
 use pixels::{PixelsBuilder, SurfaceTexture};
-use stammer::elements::{Alignment, Content, Element};
+use stammer::elements::{Alignment, Binding, Content, Element};
 use stammer::elements::{Graph, SizingStrategy};
+use stammer::event::{Key, Modifiers};
 use stammer::Panel;
 use winit::dpi::{LogicalSize, PhysicalSize};
-use winit::event::Event;
+use winit::event::{Event, VirtualKeyCode};
 use winit::event_loop::EventLoop;
 use winit::window::{Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
@@ -39,33 +38,35 @@ fn load_font(path: &str) -> std::io::Result<Font> {
     Ok(font)
 }
 
+/// Translate the keys that were pressed according to the `input` into stammer events.
+fn key_events(input: &WinitInputHelper) -> impl Iterator<Item = stammer::Event> + '_ {
+    const KEYS: [(VirtualKeyCode, Key); 4] = [
+        (VirtualKeyCode::Up, Key::Up),
+        (VirtualKeyCode::Down, Key::Down),
+        (VirtualKeyCode::Left, Key::Left),
+        (VirtualKeyCode::Right, Key::Right),
+    ];
+    let modifiers = Modifiers {
+        shift: input.held_shift(),
+        control: input.held_control(),
+        alt: input.held_alt(),
+        logo: false,
+    };
+    KEYS.into_iter()
+        .filter(|&(code, _)| input.key_pressed(code))
+        .map(move |(_, key)| stammer::Event::KeyPress { key, modifiers })
+}
+
 fn setup_elements(font: Rc<Font>) -> Element<Data> {
-    fn render_graph(lens: fn(&Data) -> &Graph) -> impl Fn(&mut Element<Data>, &Data) {
-        move |element: &mut Element<Data>, data: &Data| {
-            // TODO: This whole practice is a mess and is horrible and oh no.
-            let Content::Custom { buf, height } = &mut element.content else {
-                unreachable!()
-            };
-            let graph = lens(data);
-            graph.paint(buf, *height, &element.style);
+    fn adjust_step(_element: &mut Element<Data>, data: &mut Data, event: &stammer::Event) -> bool {
+        match event {
+            stammer::Event::KeyPress { key: Key::Up, .. } => data.rotate_step += 1,
+            stammer::Event::KeyPress { key: Key::Down, .. } => {
+                data.rotate_step = data.rotate_step.saturating_sub(1)
+            }
+            _ => return false,
         }
-    }
-
-    fn render_graph_a(element: &mut Element<Data>, data: &Data) {
-        render_graph(|data| &data.graph_a)(element, data)
-    }
-
-    fn render_graph_b(element: &mut Element<Data>, data: &Data) {
-        render_graph(|data| &data.graph_b)(element, data)
-    }
-
-    fn display_step(element: &mut Element<Data>, data: &Data) {
-        // TODO: This whole practice is a mess and is horrible and oh no.
-        let Content::Text(text, _) = &mut element.content else {
-            unreachable!()
-        };
-        text.clear();
-        text.push_str(format!("{} femtoseconds", data.rotate_step).as_str())
+        true
     }
 
     let graph_height = 16;
@@ -90,16 +91,17 @@ fn setup_elements(font: Rc<Font>) -> Element<Data> {
                         )
                         .with_padding_right(16)
                         .with_flex_right(true),
-                        Element::dynamic(
-                            display_step,
+                        Element::still(
                             Rc::clone(&font),
                             Text("---".to_string(), Alignment::Center),
-                        ),
+                        )
+                        .with_binding(Binding::text(|data: &Data| {
+                            format!("{} femtoseconds", data.rotate_step)
+                        })),
                     ]),
                 )
                 .with_minwidth(400)
-                .with_strategy(SizingStrategy::Chonker)
-                .with_padding_bottom(16),
+                .with_strategy(SizingStrategy::Chonker),
                 Element::still(
                     Rc::clone(&font),
                     Row(vec![
@@ -109,30 +111,34 @@ fn setup_elements(font: Rc<Font>) -> Element<Data> {
                                 Element::still(
                                     Rc::clone(&font),
                                     Text("deflection coil phase".to_string(), Alignment::Left),
-                                )
-                                .with_padding_bottom(16),
+                                ),
                                 Element::still(
                                     Rc::clone(&font),
                                     Text("tri-axial wave converter".to_string(), Alignment::Left),
                                 ),
                             ]),
                         )
+                        .with_gap(16)
                         .with_flex_right(true),
                         Element::still(
                             Rc::clone(&font),
                             Stack(vec![
-                                Element::dynamic(render_graph_a, Rc::clone(&font), create_graph())
-                                    .with_padding_bottom(16),
-                                Element::dynamic(render_graph_b, Rc::clone(&font), create_graph()),
+                                Element::still(Rc::clone(&font), create_graph())
+                                    .with_binding(Binding::graph(|data: &Data| &data.graph_a)),
+                                Element::still(Rc::clone(&font), create_graph())
+                                    .with_binding(Binding::graph(|data: &Data| &data.graph_b)),
                             ]),
-                        ),
+                        )
+                        .with_gap(16),
                     ]),
                 )
                 .with_minwidth(400)
                 .with_strategy(SizingStrategy::Chonker),
             ]),
         )
+        .with_gap(16)
         .with_strategy(SizingStrategy::Chonker)
+        .with_handler(adjust_step)
     }
 }
 
@@ -202,7 +208,11 @@ fn main() -> Result<(), pixels::Error> {
         [0x00, 0x00, 0x00, 0xff],
         [0xff, 0xff, 0xff, 0xff],
         data,
-    );
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("ERROR: Failed to lay out the elements: {err}");
+        std::process::exit(1);
+    });
 
     let (width, height) = (state.width, state.height);
     let size = PhysicalSize::new(width * scale_factor, height * scale_factor);
@@ -225,40 +235,30 @@ fn main() -> Result<(), pixels::Error> {
     event_loop.run(move |event, _, control_flow| {
         control_flow.set_poll();
 
-        match event {
-            // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
-            //     window.request_redraw()
-            // }
-            Event::RedrawRequested(_) => {
-                // Clear the screen before drawing.
-                pixels
-                    .frame_mut()
-                    .array_chunks_mut()
-                    .for_each(|px| *px = state.background);
-
-                // Update the state, then draw.
-                state.data_mut().update();
-                state.update();
-                state.draw(&mut pixels.frame_mut());
+        // Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) => {
+        //     window.request_redraw()
+        // }
+        if let Event::RedrawRequested(_) = event {
+            // Update the state, then draw only what changed. The frame keeps the rest.
+            state.data_mut().update();
+            if let Err(err) = state.update() {
+                eprintln!("ERROR: {err}");
+            }
+            if let Err(err) = state.draw_damaged(pixels.frame_mut()) {
+                eprintln!("ERROR: {err}");
+            }
 
-                // Try to render.
-                if let Err(err) = pixels.render() {
-                    eprintln!("ERROR: {err}");
-                    control_flow.set_exit();
-                    return;
-                }
+            // Try to render.
+            if let Err(err) = pixels.render() {
+                eprintln!("ERROR: {err}");
+                control_flow.set_exit();
+                return;
             }
-            _ => (),
         }
 
         if input.update(&event) {
-            if input.key_pressed(winit::event::VirtualKeyCode::Up) {
-                state.data_mut().rotate_step += 1;
-            }
-
-            if input.key_pressed(winit::event::VirtualKeyCode::Down) {
-                let step = &mut state.data_mut().rotate_step;
-                *step = step.saturating_sub(1);
+            for event in key_events(&input) {
+                state.handle_event(event);
             }
 
             // Close events.
@@ -0,0 +1,134 @@
+use crate::Pixel;
+
+/// How the pixels of an [`Element`](crate::elements::Element) are combined with the pixels that
+/// lie behind them, such as those of its parent.
+///
+/// All modes work on straight, non-premultiplied `[r, g, b, a]` [`Pixel`]s. For opaque pixels,
+/// [`BlendMode::SourceOver`] is the same as [`BlendMode::Replace`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Draw the pixel over what lies behind it, letting that show through according to its alpha.
+    #[default]
+    SourceOver,
+    /// Overwrite what lies behind the pixel, including its alpha.
+    Replace,
+    /// Multiply the colors of the pixel and what lies behind it, which darkens or tints it. The
+    /// result is drawn over what lies behind it like [`BlendMode::SourceOver`].
+    Multiply,
+}
+
+impl BlendMode {
+    /// Combine the `source` pixel with the `destination` pixel it is drawn onto.
+    #[inline]
+    pub fn blend(self, source: Pixel, destination: Pixel) -> Pixel {
+        match self {
+            BlendMode::Replace => source,
+            BlendMode::SourceOver => over(source, destination),
+            BlendMode::Multiply => {
+                // Where the destination is transparent, the source is left as it is.
+                let [r, g, b, a] = source;
+                let da = destination[3] as u32;
+                let mix = |s: u8, d: u8| {
+                    let product = mul(s, d) as u32;
+                    ((s as u32 * (255 - da) + product * da + 127) / 255) as u8
+                };
+                let source = [
+                    mix(r, destination[0]),
+                    mix(g, destination[1]),
+                    mix(b, destination[2]),
+                    a,
+                ];
+                over(source, destination)
+            }
+        }
+    }
+
+    /// Draw the `source` pixel onto `destination` in place.
+    #[inline]
+    pub(crate) fn apply(self, destination: &mut Pixel, source: Pixel) {
+        *destination = self.blend(source, *destination);
+    }
+
+    /// Returns whether drawing `source` simply overwrites the destination, whatever it is.
+    #[inline]
+    pub(crate) fn overwrites(self, source: Pixel) -> bool {
+        match self {
+            BlendMode::Replace => true,
+            BlendMode::SourceOver => source[3] == u8::MAX,
+            BlendMode::Multiply => false,
+        }
+    }
+}
+
+/// Multiply two channels, where `u8::MAX` stands for one.
+#[inline]
+fn mul(a: u8, b: u8) -> u8 {
+    ((a as u32 * b as u32 + 127) / 255) as u8
+}
+
+/// Composite `source` over `destination`.
+#[inline]
+fn over(source: Pixel, destination: Pixel) -> Pixel {
+    let sa = source[3] as u32;
+    match sa {
+        255 => return source,
+        0 => return destination,
+        _ => {}
+    }
+    // The destination contributes its own alpha, scaled by what the source lets through.
+    let da = destination[3] as u32 * (255 - sa) / 255;
+    let a = sa + da;
+    let channel =
+        |s: u8, d: u8| ((s as u32 * sa + d as u32 * da + a / 2) / a).min(u8::MAX as u32) as u8;
+    [
+        channel(source[0], destination[0]),
+        channel(source[1], destination[1]),
+        channel(source[2], destination[2]),
+        a as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Pixel = [0xff, 0xff, 0xff, 0xff];
+    const RED: Pixel = [0xff, 0x00, 0x00, 0xff];
+    const CLEAR: Pixel = [0x00, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn source_over() {
+        let mode = BlendMode::SourceOver;
+        assert_eq!(mode.blend(RED, WHITE), RED);
+        assert_eq!(mode.blend(CLEAR, WHITE), WHITE);
+        assert_eq!(
+            mode.blend([0x00, 0x00, 0x00, 0x80], WHITE),
+            [0x7f, 0x7f, 0x7f, 0xff]
+        );
+        // Over a transparent destination, the source keeps its own color and alpha.
+        assert_eq!(
+            mode.blend([0x10, 0x20, 0x30, 0x40], CLEAR),
+            [0x10, 0x20, 0x30, 0x40]
+        );
+    }
+
+    #[test]
+    fn replace() {
+        let mode = BlendMode::Replace;
+        assert_eq!(mode.blend(CLEAR, WHITE), CLEAR);
+        assert!(mode.overwrites(CLEAR));
+        assert!(!BlendMode::SourceOver.overwrites(CLEAR));
+    }
+
+    #[test]
+    fn multiply() {
+        let mode = BlendMode::Multiply;
+        assert_eq!(mode.blend(RED, WHITE), RED);
+        assert_eq!(mode.blend(WHITE, RED), RED);
+        assert_eq!(
+            mode.blend([0x80, 0x80, 0x80, 0xff], RED),
+            [0x80, 0x00, 0x00, 0xff]
+        );
+        assert_eq!(mode.blend(RED, CLEAR), RED);
+    }
+}
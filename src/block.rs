@@ -1,10 +1,7 @@
-use crate::{Pixel, Rows, RowsMut, PIXEL_SIZE};
+use std::ops::Range;
 
-// TODO: The methods for this trait rely on a Font argument. I think I want Font to be specified by
-// whatever Self it is implemented for.
-pub(crate) trait DrawBlock {
-    fn block(&self) -> Block;
-}
+use crate::elements::Rect;
+use crate::{BlendMode, Pixel};
 
 pub(crate) struct Block {
     /// Width in pixels.
@@ -25,71 +22,178 @@ impl Block {
         }
     }
 
-    /// Returns an iterator over the rows in this [`Block`].
-    ///
-    /// # Panics
-    ///
-    /// If `self.width` is 0, the function will panic.
-    pub(crate) fn rows(&self) -> Rows {
-        self.buf.chunks_exact(self.width as usize)
+    /// Returns a [`Canvas`] that covers this whole [`Block`].
+    pub(crate) fn canvas(&mut self) -> Canvas<'_> {
+        Canvas::new(&mut self.buf, self.width, self.height)
     }
+}
 
-    /// Returns an iterator over mutable rows in this [`Block`].
+/// A clipped view onto a borrowed pixel buffer, which is what elements paint into.
+///
+/// Positions are relative to the top left corner of the view, which may lie outside of the buffer.
+/// Only the pixels within the clip rectangle of the view are ever written to, so that painting
+/// never needs to allocate anything, and a view can be handed to an element without it having to
+/// care about what lies around it.
+///
+/// Pixels are combined with what is already in the buffer according to the [`BlendMode`] of the
+/// view, which starts out as [`BlendMode::Replace`].
+pub(crate) struct Canvas<'b> {
+    /// Row-major pixel buffer.
+    buf: &'b mut [Pixel],
+    /// The number of pixels from the start of one row in `buf` to the start of the next.
+    stride: usize,
+    /// The position of the top left corner of this view within `buf`.
+    origin: (i64, i64),
+    /// Width of the view in pixels.
+    width: u32,
+    /// Height of the view in pixels.
+    height: u32,
+    /// The area of `buf` that may be painted, which always lies within the view and `buf`.
+    clip: Rect,
+    /// How painted pixels are combined with the pixels in `buf`.
+    blend_mode: BlendMode,
+}
+
+impl<'b> Canvas<'b> {
+    /// Creates a new [`Canvas`] that covers the `buf` of `width` by `height` pixels.
     ///
     /// # Panics
     ///
-    /// If `self.width` is 0, the function will panic.
-    pub(crate) fn rows_mut(&mut self) -> RowsMut {
-        self.buf.chunks_exact_mut(self.width as usize)
+    /// If `buf` holds fewer than `width * height` pixels, this function will panic.
+    pub(crate) fn new(buf: &'b mut [Pixel], width: u32, height: u32) -> Self {
+        assert!(buf.len() >= width as usize * height as usize);
+        Self {
+            buf,
+            stride: width as usize,
+            origin: (0, 0),
+            width,
+            height,
+            clip: Rect::new(0, 0, width, height),
+            blend_mode: BlendMode::Replace,
+        }
     }
 
-    // TODO: Improve this weirdly worded doc comment.
-    // TODO: Doc comment out of date because we now also do height.
-    /// Paint another [`Block`] onto this one.
-    ///
-    /// If the remaining space in the `Block` after `start_x` is smaller than the width of the
-    /// `other`, the first pixels of `other` are drawn up to the border of `self`.
+    /// Returns the width of this view.
+    pub(crate) fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of this view.
+    pub(crate) fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns how painted pixels are combined with what lies behind them.
+    pub(crate) fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how painted pixels are combined with what lies behind them.
+    pub(crate) fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns whether none of the pixels of this view can be painted.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.clip.is_empty()
+    }
+
+    /// Restrict the pixels that can be painted to those within `clip`, which is given relative to
+    /// the top left corner of this view.
+    pub(crate) fn clipped(mut self, clip: Rect) -> Self {
+        let clip = Rect::clamped(
+            self.origin.0 + clip.x as i64,
+            self.origin.1 + clip.y as i64,
+            clip.width,
+            clip.height,
+        );
+        self.clip = self.clip.intersection(&clip);
+        self
+    }
+
+    /// Returns a view onto the area of `width` by `height` pixels at `x`, `y` of this view.
     ///
-    /// # Panics
+    /// The new view is clipped to this one, and blends like it.
+    pub(crate) fn view(&mut self, x: i64, y: i64, width: u32, height: u32) -> Canvas<'_> {
+        let origin = (self.origin.0 + x, self.origin.1 + y);
+        let area = Rect::clamped(origin.0, origin.1, width, height);
+        Canvas {
+            buf: self.buf,
+            stride: self.stride,
+            origin,
+            width,
+            height,
+            clip: self.clip.intersection(&area),
+            blend_mode: self.blend_mode,
+        }
+    }
+
+    /// Returns the range of columns of this view that can be painted.
+    pub(crate) fn visible_columns(&self) -> Range<i64> {
+        let start = self.clip.x as i64 - self.origin.0;
+        start..start + self.clip.width as i64
+    }
+
+    /// Returns the range of rows of this view that can be painted.
+    pub(crate) fn visible_rows(&self) -> Range<i64> {
+        let start = self.clip.y as i64 - self.origin.1;
+        start..start + self.clip.height as i64
+    }
+
+    /// Returns the part of row `y` that can be painted, along with the column at which it starts.
     ///
-    /// In case `start_x` is greater than the width of the `Block` that is painted onto
-    /// (`self.width`), this function will panic.
-    pub(crate) fn paint(&mut self, other: &Self, start_x: u32, start_y: u32) {
-        if other.width == 0 || self.width == 0 {
-            return;
+    /// Returns [`None`] if nothing of the row can be painted.
+    pub(crate) fn row_mut(&mut self, y: i64) -> Option<(i64, &mut [Pixel])> {
+        if self.clip.is_empty() || !self.visible_rows().contains(&y) {
+            return None;
         }
-        assert!(start_x <= self.width);
-        assert!(start_y <= self.height);
-        let delta_x = u32::min(other.width, self.width - start_x);
-        let delta_y = u32::min(other.height, self.height - start_y);
-        let end_x = start_x + delta_x;
-        for (row, other_row) in self
-            .rows_mut()
-            .skip(start_y as usize)
-            .take(delta_y as usize)
-            .zip(other.rows())
-        {
-            row[start_x as usize..end_x as usize].copy_from_slice(&other_row[..delta_x as usize])
+        let column = self.visible_columns().start;
+        let start = (self.origin.1 + y) as usize * self.stride + self.clip.x as usize;
+        Some((
+            column,
+            &mut self.buf[start..start + self.clip.width as usize],
+        ))
+    }
+
+    /// Fill this whole view with `pixel`.
+    pub(crate) fn fill(&mut self, pixel: Pixel) {
+        let blend_mode = self.blend_mode;
+        let overwrites = blend_mode.overwrites(pixel);
+        for y in self.visible_rows() {
+            let Some((_, row)) = self.row_mut(y) else {
+                continue;
+            };
+            if overwrites {
+                row.fill(pixel);
+            } else {
+                row.iter_mut().for_each(|dst| blend_mode.apply(dst, pixel));
+            }
         }
     }
 
-    /// Draws this [`Block`]s contents onto the provided pixel buffer.
-    ///
-    /// The pixel buffer is provided as a mutable slice of bytes. It is assumed that this buffer
-    /// uses the same pixel representation as [`Block`], which is 32-bit rgba pixels.
-    ///
-    /// See also: [`Pixel`].
-    pub(crate) fn draw_onto_pixels(&self, pixels: &mut [u8]) {
-        assert!(
-            pixels.len() >= self.buf.len(),
-            "pixel buffer is not large enough"
-        );
-        for (y, row) in self.rows().enumerate() {
-            let idx = y * self.width as usize * PIXEL_SIZE;
-            // TODO: See if we can get rid of this iter(). Perhaps through feature(slice_flatten)?
-            // TODO: Where should the .copied() go, ideally?
-            let row_bytes: Vec<_> = row.iter().copied().flatten().collect();
-            pixels[idx..idx + row_bytes.len()].copy_from_slice(&row_bytes);
+    /// Copy the rows of `width` pixels in `src` into this view, starting at its top left corner.
+    pub(crate) fn copy_from(&mut self, src: &[Pixel], width: u32) {
+        if width == 0 {
+            return;
+        }
+        let blend_mode = self.blend_mode;
+        for (y, src_row) in src.chunks(width as usize).enumerate() {
+            let Some((start, row)) = self.row_mut(y as i64) else {
+                continue;
+            };
+            let end = usize::min(src_row.len(), (start as usize).saturating_add(row.len()));
+            let Some(src_row) = src_row.get(start as usize..end) else {
+                continue;
+            };
+            let row = &mut row[..src_row.len()];
+            match blend_mode {
+                BlendMode::Replace => row.copy_from_slice(src_row),
+                _ => {
+                    for (dst, &src) in row.iter_mut().zip(src_row) {
+                        blend_mode.apply(dst, src);
+                    }
+                }
+            }
         }
     }
 }
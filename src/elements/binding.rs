@@ -0,0 +1,375 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use fleck::Font;
+
+use super::{Content, Element, Graph, Grid};
+
+/// An accessor that binds the content of an [`Element`] to the data `D` of a
+/// [`Panel`](crate::Panel), see [`Element::with_binding`].
+///
+/// When the [`Panel`](crate::Panel) is updated, the content takes on the value that the accessor
+/// returns. The value is only written when it differs from what the content already shows, so an
+/// `Element` whose value did not change is neither rewrapped, repainted, nor redrawn.
+///
+/// A binding only applies to the matching content. Bound to any other content, it does nothing.
+/// The accessors may be closures that capture state of their own, so that one accessor can be
+/// reused for different parts of the data.
+pub enum Binding<D> {
+    /// Binds the string of a [`Content::Text`].
+    Text(Box<dyn Fn(&D) -> String>),
+    /// Binds the text of a [`Content::Paragraph`]. Only the lines from the first change onward
+    /// are rewrapped, see [`WrappedText::set_text`](super::WrappedText::set_text).
+    Paragraph(Box<dyn Fn(&D) -> &str>),
+    /// Binds a [`Graph`] that is painted into a [`Content::Custom`]. The buffer is resized to
+    /// match the length of the `Graph`, keeping its height.
+    Graph(Box<dyn Fn(&D) -> &Graph>),
+    /// Binds the children of a [`Content::Row`], [`Content::Stack`], [`Content::Layers`] or
+    /// [`Content::Grid`] to a collection in the data, see [`ListBinding`].
+    List(ListBinding<D>),
+}
+
+/// Returns the key of the item at `index` of a collection in the data, see [`ListBinding`].
+type BoxedKeyFn<D> = Box<dyn Fn(&D, usize) -> &str>;
+/// Builds the child for the item at `index` of a collection in the data, see [`ListBinding`].
+type BoxedTemplateFn<D> = Box<dyn Fn(&D, usize, &Rc<Font>) -> Element<D>>;
+
+/// Accessors that build the children of a container from a collection in the data `D` of a
+/// [`Panel`](crate::Panel), one child per item, see [`Binding::List`].
+///
+/// Every item is identified by its `key`. When the [`Panel`](crate::Panel) is updated, the
+/// children are reconciled with the items by their keys: a child whose key is still there is kept
+/// as it is, along with its scroll, focus and other state, and moved to the position of its item.
+/// Only items with new keys are built through the `template`, and the children of keys that are
+/// gone are dropped. When the keys did not change, nothing is done at all, and nothing is
+/// allocated either, since the keys are borrowed from the data. Of items that share a key, only
+/// the first keeps its child.
+///
+/// Any children that the container starts out with are replaced at the first update.
+pub struct ListBinding<D> {
+    /// Returns the number of items.
+    pub len: Box<dyn Fn(&D) -> usize>,
+    /// Returns the key of the item at the index, as it is found in the data.
+    pub key: BoxedKeyFn<D>,
+    /// Build the child for the item at the index, in the font of the container.
+    ///
+    /// A child that is built without an id is given its key as its id, so that it can be found
+    /// with [`Element::get`], and so that its update function can tell which item it shows.
+    pub template: BoxedTemplateFn<D>,
+}
+
+impl<D> ListBinding<D> {
+    /// Creates a new [`ListBinding`]. Like the accessors of a [`Binding`], these may be closures
+    /// that capture state of their own.
+    pub fn new(
+        len: impl Fn(&D) -> usize + 'static,
+        key: impl Fn(&D, usize) -> &str + 'static,
+        template: impl Fn(&D, usize, &Rc<Font>) -> Element<D> + 'static,
+    ) -> Self {
+        Self {
+            len: Box::new(len),
+            key: Box::new(key),
+            template: Box::new(template),
+        }
+    }
+}
+
+impl<D> Binding<D> {
+    /// Creates a [`Binding::Text`].
+    pub fn text(get: impl Fn(&D) -> String + 'static) -> Self {
+        Self::Text(Box::new(get))
+    }
+
+    /// Creates a [`Binding::Paragraph`].
+    pub fn paragraph(get: impl Fn(&D) -> &str + 'static) -> Self {
+        Self::Paragraph(Box::new(get))
+    }
+
+    /// Creates a [`Binding::Graph`].
+    pub fn graph(get: impl Fn(&D) -> &Graph + 'static) -> Self {
+        Self::Graph(Box::new(get))
+    }
+}
+
+/// A [`Binding`] along with what it last wrote into the content of its [`Element`].
+pub(super) struct Bound<D> {
+    binding: Binding<D>,
+    /// A hash of the [`Graph`] that was last painted, and the colors it was painted in.
+    painted: Option<u64>,
+    /// The keys of the children that were built through a [`ListBinding`], in order.
+    keys: Vec<String>,
+}
+
+impl<D> From<Binding<D>> for Bound<D> {
+    fn from(binding: Binding<D>) -> Self {
+        Self {
+            binding,
+            painted: None,
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl<D> Element<D> {
+    /// Write the value of the [`Binding`] of this [`Element`], if it has one, into its content.
+    pub(super) fn read_binding(&mut self, data: &D) {
+        let Some(bound) = &mut self.binding else {
+            return;
+        };
+        match (&bound.binding, &mut self.content) {
+            (Binding::Text(get), Content::Text(text, _)) => {
+                let value = get(data);
+                if *text != value {
+                    *text = value;
+                }
+            }
+            (Binding::Paragraph(get), Content::Paragraph(wrapped, _)) => {
+                wrapped.set_text(get(data), &self.style.font)
+            }
+            (Binding::Graph(get), Content::Custom { buf, height }) => {
+                let graph = get(data);
+                let mut state = DefaultHasher::new();
+                graph
+                    .iter()
+                    .for_each(|value| value.to_bits().hash(&mut state));
+                (graph.min().to_bits(), graph.max().to_bits()).hash(&mut state);
+                (self.style.foreground, self.style.background).hash(&mut state);
+                let hash = state.finish();
+                if bound.painted == Some(hash) {
+                    return;
+                }
+                buf.resize(graph.len() * *height as usize, self.style.background);
+                graph.paint(buf, *height, &self.style);
+                bound.painted = Some(hash);
+            }
+            (
+                Binding::List(list),
+                Content::Row(children)
+                | Content::Stack(children)
+                | Content::Layers(children)
+                | Content::Grid(Grid { children, .. }),
+            ) => {
+                // The keys are only collected once they turn out to have changed.
+                let len = (list.len)(data);
+                let unchanged = len == bound.keys.len()
+                    && (0..len).all(|idx| (list.key)(data, idx) == bound.keys[idx]);
+                if unchanged {
+                    return;
+                }
+                let keys: Vec<String> = (0..len)
+                    .map(|idx| (list.key)(data, idx).to_string())
+                    .collect();
+                reconcile(children, &bound.keys, &keys, |idx| {
+                    (list.template)(data, idx, &self.style.font)
+                });
+                bound.keys = keys;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rearrange the `children`, which belong to the `old` keys, such that they belong to the `new`
+/// keys. Children are kept for the keys that are in both, and built by `build` from the index of
+/// their new key otherwise.
+fn reconcile<D>(
+    children: &mut Vec<Element<D>>,
+    old: &[String],
+    new: &[String],
+    mut build: impl FnMut(usize) -> Element<D>,
+) {
+    // Children without a key, such as those the container started out with, are not kept.
+    let mut kept: HashMap<&str, Element<D>> = HashMap::new();
+    for (key, child) in old.iter().zip(children.drain(..)) {
+        kept.entry(key).or_insert(child);
+    }
+    children.extend(new.iter().enumerate().map(|(idx, key)| {
+        kept.remove(key.as_str()).unwrap_or_else(|| {
+            let mut child = build(idx);
+            child.id.get_or_insert_with(|| key.clone());
+            child
+        })
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::elements::builder::ElementBuilder;
+    use crate::elements::{Alignment, WrappedText};
+    use crate::testing::font;
+    use crate::{Panel, Pixel};
+
+    struct Data {
+        count: usize,
+        story: String,
+        graph: Graph,
+    }
+
+    fn panel() -> Panel<Data> {
+        let font = font();
+        // The accessors may capture state of their own.
+        let unit = "lines";
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(String::new(), Alignment::Left),
+                )
+                .with_binding(Binding::text(move |data: &Data| {
+                    format!("{} {unit}", data.count)
+                })),
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Paragraph(WrappedText::new(String::new(), 80, &font), Alignment::Left),
+                )
+                .with_binding(Binding::paragraph(|data: &Data| &data.story)),
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Custom {
+                        buf: Vec::new(),
+                        height: 4,
+                    },
+                )
+                .with_binding(Binding::graph(|data: &Data| &data.graph)),
+            ]),
+        );
+        let data = Data {
+            count: 3,
+            story: "Once upon a time".to_string(),
+            graph: Graph::new(5).with_range(0.0, 3.0),
+        };
+        let mut panel = Panel::new(elements, [0x00; 4], [0xff; 4], data).unwrap();
+        // Nothing is bound before the first update, so the panel cannot size itself to it.
+        panel.resize(80, 60);
+        panel
+    }
+
+    #[test]
+    fn values_are_written() {
+        let mut panel = panel();
+        panel.update().unwrap();
+        let children = panel.elements.content.children();
+        assert!(matches!(&children[0].content, Content::Text(text, _) if text == "3 lines"));
+        assert!(
+            matches!(&children[1].content, Content::Paragraph(wrapped, _) if wrapped.text() == "Once upon a time")
+        );
+        let Content::Custom { buf, .. } = &children[2].content else {
+            unreachable!()
+        };
+        assert_eq!(buf.len(), 5 * 4);
+        assert_eq!(children[2].fill_size().width, 5);
+    }
+
+    #[test]
+    fn only_changes_are_drawn() {
+        let mut panel = panel();
+        panel.update().unwrap();
+        let mut pixels = vec![0; (panel.width * panel.height) as usize * 4];
+        panel.draw_damaged(&mut pixels).unwrap();
+
+        // The same values leave everything as it was.
+        panel.update().unwrap();
+        assert!(panel.draw_damaged(&mut pixels).unwrap().is_empty());
+
+        // A changed graph is painted again.
+        panel.data_mut().graph.push(2.0);
+        panel.update().unwrap();
+        let (_, y) = panel.elements.content.children()[2].position();
+        let damage = panel.draw_damaged(&mut pixels).unwrap();
+        assert_eq!(damage.len(), 1);
+        assert_eq!(damage[0].y, y as u32);
+    }
+
+    fn list() -> Panel<Vec<&'static str>> {
+        let font = font();
+        // The template may capture state of its own.
+        let locked = "locked";
+        let elements = Element::stack_builder(&font)
+            .with_binding(
+                |data: &Vec<&str>| data.len(),
+                |data, idx| data[idx],
+                move |data, idx, font| {
+                    let text = Content::Text(data[idx].to_string(), Alignment::Left);
+                    Element::still(Rc::clone(font), text)
+                        .with_focusable(true)
+                        .with_update(move |element, data: &Vec<&str>| {
+                            element.focusable = !data.contains(&locked)
+                        })
+                },
+            )
+            .build();
+        let mut panel = Panel::new(elements, [0x00; 4], [0xff; 4], vec!["a", "b", "c"]).unwrap();
+        panel.resize(80, 80);
+        panel.update().unwrap();
+        panel
+    }
+
+    /// A background that no child starts out with, to tell a child that was kept apart.
+    const MARK: Pixel = [0x12, 0x34, 0x56, 0xff];
+
+    fn texts<D>(element: &Element<D>) -> Vec<&str> {
+        let children = element.content.children();
+        children
+            .iter()
+            .map(|child| match &child.content {
+                Content::Text(text, _) => text.as_str(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn children_are_kept_by_key() {
+        let mut panel = list();
+        assert_eq!(texts(&panel.elements), ["a", "b", "c"]);
+        assert_eq!(panel.elements.path_of("c"), Some(vec![2]));
+
+        // Mark a child, so that we can tell it is kept rather than built again.
+        panel.get_mut("b").unwrap().style.background = MARK;
+        assert!(panel.focus(&[1]));
+
+        *panel.data_mut() = vec!["c", "b", "d"];
+        panel.update().unwrap();
+        assert_eq!(texts(&panel.elements), ["c", "b", "d"]);
+        assert_eq!(panel.get("b").unwrap().style.background, MARK);
+        assert_ne!(panel.get("d").unwrap().style.background, MARK);
+
+        // The focus follows the child to its new position.
+        *panel.data_mut() = vec!["b", "d"];
+        panel.update().unwrap();
+        assert_eq!(texts(&panel.elements), ["b", "d"]);
+        assert_eq!(panel.focused(), Some([0].as_slice()));
+        assert!(panel.get("b").unwrap().is_focused());
+        assert!(!panel.get("d").unwrap().is_focused());
+    }
+
+    #[test]
+    fn focus_is_dropped_when_unfocusable() {
+        let mut panel = list();
+        assert!(panel.focus(&[1]));
+
+        // The focused child moves, but can no longer be focused on its way.
+        *panel.data_mut() = vec!["b", "locked"];
+        panel.update().unwrap();
+        assert_eq!(panel.focused(), None);
+        assert!(!panel.get("b").unwrap().is_focused());
+    }
+
+    #[test]
+    fn focus_is_dropped_when_removed() {
+        let mut panel = list();
+        assert!(panel.focus(&[1]));
+
+        // The child that slides into the place of the focused one does not take over the focus.
+        *panel.data_mut() = vec!["a", "c"];
+        panel.update().unwrap();
+        assert_eq!(panel.focused(), None);
+        assert!(!panel.get("c").unwrap().is_focused());
+    }
+}
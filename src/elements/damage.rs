@@ -0,0 +1,281 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use super::{Content, Dimensions, Element, Grid, VirtualList};
+
+/// A rectangular area of a [`Panel`](crate::Panel), in pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Creates a new [`Rect`].
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a [`Rect`] of `width` by `height` at a position that may be negative, cutting off
+    /// the part that lies above or to the left of the origin.
+    pub(crate) fn clamped(x: i64, y: i64, width: u32, height: u32) -> Self {
+        let clamp = |v: i64| v.clamp(0, u32::MAX as i64) as u32;
+        let (x0, y0) = (clamp(x), clamp(y));
+        let (x1, y1) = (clamp(x + width as i64), clamp(y + height as i64));
+        Self::new(x0, y0, x1 - x0, y1 - y0)
+    }
+
+    /// Returns whether this [`Rect`] covers no pixels at all.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns the area that is covered by both `self` and `other`.
+    ///
+    /// If they do not overlap, the returned [`Rect`] is empty.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        Self::new(x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+    }
+
+    /// Returns whether `other` lies entirely within this [`Rect`].
+    pub fn contains(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+}
+
+/// What an [`Element`] looked like the last time damage was collected for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Drawn {
+    /// The visible area the [`Element`] covered.
+    rect: Rect,
+    /// A hash of everything that determines how the [`Element`] itself is drawn.
+    appearance: u64,
+}
+
+impl<D> Element<D> {
+    /// Find the areas that need to be redrawn because this [`Element`] or any of its descendants
+    /// changed since damage was last collected, and push them onto `damage`.
+    ///
+    /// The `Element` is placed with its top left corner at `x`, `y`, and only the part within
+    /// `clip` is considered to be visible. When an `Element` moved, both its old and new areas
+    /// are damaged.
+    pub(crate) fn collect_damage(&mut self, x: i64, y: i64, clip: Rect, damage: &mut Vec<Rect>) {
+        let Dimensions { width, height } = self.overall_size();
+        let rect = clip.intersection(&Rect::clamped(x, y, width, height));
+        let drawn = Drawn {
+            rect,
+            appearance: self.appearance(),
+        };
+        match self.drawn.replace(drawn) {
+            Some(old) if old == drawn => {}
+            Some(old) => damage.extend([old.rect, rect]),
+            None => damage.push(rect),
+        }
+
+        let Dimensions { width, height } = self.fill_size();
+        let inner = rect.intersection(&Rect::clamped(
+            x + self.padding.left as i64,
+            y + self.padding.top as i64,
+            width,
+            height,
+        ));
+        let (scroll_x, scroll_y) = self.scroll_offset();
+        let x = x + self.padding.left as i64 - scroll_x as i64;
+        let y = y + self.padding.top as i64 - scroll_y as i64;
+        for child in self.content.children_mut() {
+            let (cx, cy) = child.position;
+            child.collect_damage(x + cx, y + cy, inner, damage);
+        }
+    }
+
+    /// Returns a hash of everything that determines how this [`Element`] itself is drawn, apart
+    /// from its position and size.
+    ///
+    /// For a container, such as a [`Content::Row`] or [`Content::Grid`], this includes the layout
+    /// of the children, so that the whole `Element` is redrawn when children are added, removed or
+    /// moved around.
+    fn appearance(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.colors().hash(&mut state);
+        Rc::as_ptr(&self.style.font).hash(&mut state);
+        self.style.blend_mode.hash(&mut state);
+        self.padding.hash(&mut state);
+        (self.scroll, self.scroll_x, self.scrollbar).hash(&mut state);
+        self.focused.hash(&mut state);
+        match &self.content {
+            Content::Text(text, alignment) => (text, alignment).hash(&mut state),
+            Content::Paragraph(wrapped, alignment) => {
+                (wrapped.revision(), alignment, self.size.maxheight).hash(&mut state)
+            }
+            Content::Custom { buf, height } => (buf.as_flattened(), height).hash(&mut state),
+            Content::Input(input, _) => input.hash(&mut state),
+            Content::Table(table, _) => table.hash(&mut state),
+            Content::Editor(editor, _) => (
+                editor.wrapped().revision(),
+                editor.cursor(),
+                editor.selection(),
+            )
+                .hash(&mut state),
+            Content::Row(children)
+            | Content::Stack(children)
+            | Content::Layers(children)
+            | Content::Grid(Grid { children, .. })
+            | Content::VirtualList(VirtualList { children, .. }) => {
+                children.len().hash(&mut state);
+                for child in children {
+                    let Dimensions { width, height } = child.overall_size();
+                    (child.position, width, height).hash(&mut state);
+                }
+            }
+        }
+        state.finish()
+    }
+}
+
+/// Remove the empty [`Rect`]s from `damage`, as well as those that lie within another one.
+pub(crate) fn simplify_damage(damage: &mut Vec<Rect>) {
+    damage.retain(|rect| !rect.is_empty());
+    damage.sort_unstable_by_key(|rect| std::cmp::Reverse(rect.width as u64 * rect.height as u64));
+    let mut idx = 0;
+    while idx < damage.len() {
+        let rect = damage[idx];
+        let covered = damage[..idx].iter().any(|other| other.contains(&rect));
+        if covered {
+            damage.remove(idx);
+        } else {
+            idx += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::Alignment;
+    use crate::testing::font;
+    use crate::{Panel, PIXEL_SIZE};
+
+    type Data = [String; 2];
+
+    fn panel() -> Panel<Data> {
+        fn line(element: &mut Element<Data>, data: &Data, idx: usize) {
+            if let Content::Text(text, _) = &mut element.content {
+                text.clone_from(&data[idx]);
+            }
+        }
+        let font = font();
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                Element::dynamic(
+                    |element, data| line(element, data, 0),
+                    Rc::clone(&font),
+                    Content::Text(String::new(), Alignment::Left),
+                ),
+                Element::dynamic(
+                    |element, data| line(element, data, 1),
+                    Rc::clone(&font),
+                    Content::Text(String::new(), Alignment::Left),
+                )
+                .with_background([0x00, 0x00, 0xff, 0xff]),
+            ]),
+        );
+        let data = ["first".to_string(), "1234".to_string()];
+        let mut panel = Panel::new(elements, [0; 4], [0xff; 4], data).unwrap();
+        panel.resize(40, 40);
+        panel.update().unwrap();
+        panel
+    }
+
+    /// Returns the pixels of a full draw of `panel`, to compare a damaged draw against.
+    fn full_draw(panel: &Panel<Data>) -> Vec<u8> {
+        let mut pixels = vec![0; 40 * 40 * PIXEL_SIZE];
+        panel.draw(&mut pixels).unwrap();
+        pixels
+    }
+
+    #[test]
+    fn only_changes_are_damaged() {
+        let mut panel = panel();
+        let mut pixels = vec![0; 40 * 40 * PIXEL_SIZE];
+        let damage = panel.draw_damaged(&mut pixels).unwrap();
+        assert_eq!(damage, [Rect::new(0, 0, 40, 40)]);
+        assert_eq!(pixels, full_draw(&panel));
+        assert!(panel.draw_damaged(&mut pixels).unwrap().is_empty());
+
+        // Replacing the text with one of the same size only damages that line.
+        panel.data_mut()[1] = "4321".to_string();
+        panel.update().unwrap();
+        let font = font();
+        let height = font.height() as u32;
+        let width = font.determine_width("4321") as u32;
+        let damage = panel.draw_damaged(&mut pixels).unwrap();
+        assert_eq!(damage, [Rect::new(0, height, width, height)]);
+        assert_eq!(pixels, full_draw(&panel));
+
+        // A change of style is damage as well.
+        panel.elements.content.children_mut()[0].style.foreground = [0xff, 0x00, 0x00, 0xff];
+        let damage = panel.draw_damaged(&mut pixels).unwrap();
+        assert_eq!(damage.len(), 1);
+        assert_eq!(damage[0].y, 0);
+        assert_eq!(pixels, full_draw(&panel));
+    }
+
+    #[test]
+    fn removed_children_are_damaged() {
+        let mut panel = panel();
+        let mut pixels = vec![0; 40 * 40 * PIXEL_SIZE];
+        panel.draw_damaged(&mut pixels).unwrap();
+        if let Content::Stack(children) = &mut panel.elements.content {
+            children.pop();
+        }
+        panel.update().unwrap();
+        assert!(!panel.draw_damaged(&mut pixels).unwrap().is_empty());
+        assert_eq!(pixels, full_draw(&panel));
+
+        // A resize redraws everything.
+        panel.resize(20, 30);
+        let mut pixels = vec![0; 20 * 30 * PIXEL_SIZE];
+        let damage = panel.draw_damaged(&mut pixels).unwrap();
+        assert_eq!(damage, [Rect::new(0, 0, 20, 30)]);
+    }
+
+    #[test]
+    fn rect_intersection() {
+        let a = Rect::new(0, 0, 10, 10);
+        assert_eq!(
+            a.intersection(&Rect::new(5, 6, 10, 10)),
+            Rect::new(5, 6, 5, 4)
+        );
+        assert!(a.intersection(&Rect::new(10, 0, 4, 4)).is_empty());
+        assert_eq!(Rect::clamped(-3, 2, 5, 5), Rect::new(0, 2, 2, 5));
+        assert!(Rect::clamped(-8, 0, 5, 5).is_empty());
+    }
+
+    #[test]
+    fn simplify() {
+        let mut damage = vec![
+            Rect::new(2, 2, 2, 2),
+            Rect::new(0, 0, 0, 5),
+            Rect::new(0, 0, 8, 8),
+            Rect::new(6, 6, 4, 4),
+        ];
+        simplify_damage(&mut damage);
+        assert_eq!(damage, [Rect::new(0, 0, 8, 8), Rect::new(6, 6, 4, 4)]);
+    }
+}
@@ -0,0 +1,299 @@
+use super::text_input::InputResponse;
+use super::{Content, Element};
+use crate::event::Event;
+
+impl<D> Element<D> {
+    /// Dispatch an [`Event`] to the [`Element`] at `path`, relative to this `Element`.
+    ///
+    /// The `Event` is first passed to the handler of the `Element` at the end of the `path`. For
+    /// as long as it is not handled, it bubbles up to the handlers of its ancestors, ending with
+    /// this `Element`. Along the way, the position of the `Event` is translated such that each
+    /// handler receives it relative to its own `Element`.
+    ///
+    /// A [`Content::Input`] or [`Content::Editor`] applies the `Event` to itself when its handler
+    /// leaves it unhandled, and writes the edited text back to the `data` if it is bound to it. A
+    /// [`Content::Table`] moves its selection in the same way. Before either of those, a
+    /// [`Scrollbar`](super::Scrollbar) responds to pointer events on its bars.
+    ///
+    /// Returns whether any handler handled the `Event`.
+    pub(crate) fn dispatch(&mut self, path: &[usize], event: Event, data: &mut D) -> bool {
+        if let Some((&index, rest)) = path.split_first() {
+            if let Some((dx, dy)) = self.child_offset(index) {
+                let child = &mut self.content.children_mut()[index];
+                if child.dispatch(rest, event.translated(dx, dy), data) {
+                    return true;
+                }
+            }
+        }
+
+        let handled = match self.handler {
+            Some(handler) => handler(self, data, &event),
+            None => false,
+        };
+        handled
+            || self.drag_scrollbar(event)
+            || self.edit_text(event, data)
+            || self.select_row(event)
+    }
+
+    /// Apply an [`Event`] to the [`Content::Input`] or [`Content::Editor`] of this [`Element`],
+    /// if it has one. An editor is scrolled to keep the line of its cursor in view.
+    ///
+    /// Returns whether the input or editor responded to the `Event`.
+    fn edit_text(&mut self, event: Event, data: &mut D) -> bool {
+        let fill = self.fill_size();
+        let (scroll_x, scroll_y) = self.scroll_offset();
+        let font = &self.style.font;
+        // The text expects positions relative to itself, and it starts within the padding, moved
+        // by the scroll of the element.
+        let event = event.translated(
+            self.padding.left as i64 - scroll_x as i64,
+            self.padding.top as i64 - scroll_y as i64,
+        );
+        let (response, text, binding) = match &mut self.content {
+            Content::Input(input, binding) => {
+                let response = input.handle_event(&event, font);
+                input.scroll_into_view(fill.width, font);
+                (response, input.text(), binding)
+            }
+            Content::Editor(editor, binding) => {
+                let response = editor.handle_event(&event, font);
+                if response != InputResponse::Ignored {
+                    let scroll = editor.scroll_into_view(scroll_y, fill.height, font);
+                    if scroll != scroll_y {
+                        self.scroll = Some(scroll);
+                    }
+                }
+                (response, editor.text(), binding)
+            }
+            _ => return false,
+        };
+        if response == InputResponse::Edited {
+            if let Some(binding) = binding {
+                let value = (binding.get_mut)(data);
+                value.clear();
+                value.push_str(text);
+            }
+        }
+        response != InputResponse::Ignored
+    }
+
+    /// Apply an [`Event`] to the selection of the [`Content::Table`] of this [`Element`], if it
+    /// has one, and scroll to keep the selected row in view.
+    ///
+    /// Returns whether the table responded to the `Event`.
+    fn select_row(&mut self, event: Event) -> bool {
+        let fill_height = self.fill_size().height;
+        let font = &self.style.font;
+        let Content::Table(table, _) = &mut self.content else {
+            return false;
+        };
+        let event = event.translated(self.padding.left as i64, self.padding.top as i64);
+        let first = (self.scroll.unwrap_or_default() as usize).min(table.rows());
+        let page = table.page_rows(fill_height, font);
+        if !table.handle_event(&event, font, first, page) {
+            return false;
+        }
+        self.scroll = Some(table.scroll_to_selection(first, page) as u32);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::elements::builder::ElementBuilder;
+    use crate::elements::{Alignment, Content};
+    use crate::event::{Button, Key, Modifiers};
+    use crate::testing::font;
+    use crate::Panel;
+
+    type Data = Vec<String>;
+
+    fn log_parent(_: &mut Element<Data>, data: &mut Data, event: &Event) -> bool {
+        data.push(format!("parent {event:?}"));
+        true
+    }
+
+    fn log_child(element: &mut Element<Data>, data: &mut Data, event: &Event) -> bool {
+        data.push(format!("child {event:?}"));
+        // Only handle presses, so the other events bubble up.
+        let handled = matches!(event, Event::PointerPress { .. });
+        if handled {
+            element.style.background = [0xff, 0x00, 0x00, 0xff];
+        }
+        handled
+    }
+
+    fn panel() -> Panel<Data> {
+        let font = font();
+        let text = |s: &str| {
+            Element::still(
+                Rc::clone(&font),
+                Content::Text(s.to_string(), Alignment::Left),
+            )
+        };
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                text("first").with_padding_bottom(4),
+                text("second").with_padding_left(8).with_handler(log_child),
+            ]),
+        )
+        .with_padding_top(2)
+        .with_handler(log_parent);
+        Panel::new(elements, [0; 4], [0xff; 4], Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn pointer_events_bubble() {
+        let mut panel = panel();
+
+        // The second child starts at 2 + 16 + 4 = 22.
+        let press = Event::PointerPress {
+            x: 10,
+            y: 25,
+            button: Button::Left,
+        };
+        assert!(panel.handle_event(press));
+        let moved = Event::PointerMove { x: 10, y: 25 };
+        assert!(panel.handle_event(moved));
+        assert_eq!(
+            panel.data_mut().as_slice(),
+            [
+                "child PointerPress { x: 10, y: 3, button: Left }",
+                "child PointerMove { x: 10, y: 3 }",
+                "parent PointerMove { x: 10, y: 25 }",
+            ]
+        );
+        let Content::Stack(children) = &panel.elements.content else {
+            unreachable!()
+        };
+        assert_eq!(children[1].style.background, [0xff, 0x00, 0x00, 0xff]);
+
+        // The handled press grabbed the pointer for the second child, until it is released.
+        panel.data_mut().clear();
+        let release = Event::PointerRelease {
+            x: 1,
+            y: 3,
+            button: Button::Left,
+        };
+        assert!(panel.handle_event(release));
+        assert_eq!(
+            panel.data_mut().first().map(String::as_str),
+            Some("child PointerRelease { x: 1, y: 0, button: Left }")
+        );
+
+        // The first child has no handler, so the parent gets to handle this one.
+        panel.data_mut().clear();
+        assert!(panel.handle_event(Event::PointerMove { x: 1, y: 3 }));
+        assert_eq!(
+            panel.data_mut().as_slice(),
+            ["parent PointerMove { x: 1, y: 3 }"]
+        );
+
+        // Outside of the panel, nobody gets anything.
+        panel.data_mut().clear();
+        let outside = Event::PointerMove {
+            x: panel.width,
+            y: 0,
+        };
+        assert!(!panel.handle_event(outside));
+        assert!(panel.data_mut().is_empty());
+    }
+
+    #[test]
+    fn keys_and_resizes_go_to_the_root() {
+        let mut panel = panel();
+        let key = Event::KeyPress {
+            key: Key::Enter,
+            modifiers: Modifiers::default(),
+        };
+        assert!(panel.handle_event(key));
+        assert!(panel.handle_event(Event::Resize {
+            width: 40,
+            height: 30
+        }));
+        assert_eq!((panel.width, panel.height), (40, 30));
+        assert_eq!(panel.data_mut().len(), 2);
+        assert!(panel.data_mut()[0].starts_with("parent KeyPress"));
+        assert!(panel.data_mut()[1].starts_with("parent Resize"));
+    }
+
+    #[test]
+    fn bound_input() {
+        let font = font();
+        let elements = Element::stack_builder(&font)
+            .add_child(
+                Element::text_input("", &font)
+                    .with_binding(|data: &Data| &data[0], |data| &mut data[0])
+                    .build(),
+            )
+            .build();
+        let data = vec!["abc".to_string()];
+        let mut panel = Panel::new(elements, [0; 4], [0xff; 4], data).unwrap();
+        panel.update().unwrap();
+        assert!(panel.focus(&[0]));
+
+        let backspace = Event::KeyPress {
+            key: Key::Backspace,
+            modifiers: Modifiers::default(),
+        };
+        assert!(panel.handle_event(backspace));
+        assert!(panel.handle_event(Event::Text('d')));
+        assert_eq!(panel.data_mut().as_slice(), ["abd"]);
+
+        // A change to the data is picked up by the input on the next update.
+        panel.data_mut()[0] = "xyz".to_string();
+        panel.update().unwrap();
+        let Content::Input(input, _) = &panel.elements.content.children()[0].content else {
+            unreachable!()
+        };
+        assert_eq!(input.text(), "xyz");
+    }
+
+    #[test]
+    fn scrolled_editor() {
+        let font = font();
+        let elements = Element::<Data>::text_editor("one\ntwo\nthree\nfour\nfive", &font)
+            .build()
+            .with_fixedheight(32)
+            .with_padding_top(2)
+            .with_scroll(32);
+        let mut panel = Panel::new(elements, [0; 4], [0xff; 4], Vec::new()).unwrap();
+        panel.update().unwrap();
+
+        // The third line is scrolled to the top, just below the padding.
+        let press = Event::PointerPress {
+            x: 0,
+            y: 6,
+            button: Button::Left,
+        };
+        assert!(panel.handle_event(press));
+        let Content::Editor(editor, _) = &panel.elements.content else {
+            unreachable!()
+        };
+        assert_eq!(editor.cursor(), "one\ntwo\n".len());
+
+        // Moving down past the bottom scrolls the line of the cursor into view.
+        let down = Event::KeyPress {
+            key: Key::Down,
+            modifiers: Modifiers::default(),
+        };
+        assert!(panel.handle_event(down));
+        assert_eq!(panel.elements.scroll, Some(32));
+        assert!(panel.handle_event(down));
+        assert_eq!(panel.elements.scroll, Some(48));
+
+        // Typing on a line above the view scrolls back up to it.
+        let up = Event::KeyPress {
+            key: Key::Up,
+            modifiers: Modifiers::default(),
+        };
+        (0..4).for_each(|_| assert!(panel.handle_event(up)));
+        assert!(panel.handle_event(Event::Text('!')));
+        assert_eq!(panel.elements.scroll, Some(0));
+    }
+}
@@ -0,0 +1,224 @@
+use super::{Element, FocusStyle};
+use crate::Pixel;
+
+impl<D> Element<D> {
+    /// Returns whether this [`Element`] currently holds the keyboard focus of its
+    /// [`Panel`](crate::Panel).
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Returns the `foreground` and `background` this [`Element`] is drawn with, taking its
+    /// `focus_style` into account.
+    pub(crate) fn colors(&self) -> (Pixel, Pixel) {
+        let (foreground, background) = (self.style.foreground, self.style.background);
+        match self.focus_style {
+            Some(focus_style) if self.focused => match focus_style {
+                FocusStyle::Swap => (background, foreground),
+                FocusStyle::Colors {
+                    foreground,
+                    background,
+                } => (foreground, background),
+            },
+            _ => (foreground, background),
+        }
+    }
+
+    /// Returns the descendant at `path`, relative to this [`Element`].
+    ///
+    /// An empty path refers to this `Element` itself. Returns [`None`] if the path does not lead
+    /// to an `Element`.
+    pub fn descendant(&self, path: &[usize]) -> Option<&Element<D>> {
+        path.iter().try_fold(self, |element, &index| {
+            element.content.children().get(index)
+        })
+    }
+
+    /// Returns the descendant at `path` mutably.
+    ///
+    /// See [`Element::descendant`].
+    pub fn descendant_mut(&mut self, path: &[usize]) -> Option<&mut Element<D>> {
+        path.iter().try_fold(self, |element, &index| {
+            element.content.children_mut().get_mut(index)
+        })
+    }
+
+    /// Returns the paths of all focusable [`Element`]s in this tree, in tree order.
+    ///
+    /// Tree order visits an `Element` before its children, and the children in order. This is the
+    /// order in which focus moves when traversing with Tab.
+    pub(crate) fn focusable_paths(&self) -> Vec<Vec<usize>> {
+        fn collect<D>(element: &Element<D>, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+            if element.focusable {
+                paths.push(path.clone());
+            }
+            for (idx, child) in element.content.children().iter().enumerate() {
+                path.push(idx);
+                collect(child, path, paths);
+                path.pop();
+            }
+        }
+
+        let mut paths = Vec::new();
+        collect(self, &mut Vec::new(), &mut paths);
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::elements::{Alignment, Content};
+    use crate::event::{Button, Event, Key, Modifiers};
+    use crate::testing::font;
+    use crate::Panel;
+
+    const RED: Pixel = [0xff, 0x00, 0x00, 0xff];
+
+    fn record(element: &mut Element<Vec<usize>>, data: &mut Vec<usize>, event: &Event) -> bool {
+        let Event::Text(ch) = event else {
+            return false;
+        };
+        let Content::Text(text, _) = &element.content else {
+            return false;
+        };
+        // Each field records the length of its text, and only handles digits.
+        data.push(text.len());
+        ch.is_ascii_digit()
+    }
+
+    fn panel() -> Panel<Vec<usize>> {
+        let font = font();
+        let field = |len: usize| {
+            Element::still(
+                Rc::clone(&font),
+                Content::Text("x".repeat(len), Alignment::Left),
+            )
+            .with_focusable(true)
+            .with_handler(record)
+        };
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                field(1),
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Row(vec![field(2), field(3).with_focus_style(FocusStyle::Swap)]),
+                ),
+                field(4).with_focusable(false),
+            ]),
+        )
+        .with_handler(|_, data, _| {
+            data.push(0);
+            true
+        });
+        Panel::new(elements, [0; 4], [0xff; 4], Vec::new()).unwrap()
+    }
+
+    fn tab(shift: bool) -> Event {
+        Event::KeyPress {
+            key: Key::Tab,
+            modifiers: Modifiers {
+                shift,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn tree_order() {
+        let panel = panel();
+        assert_eq!(
+            panel.elements.focusable_paths(),
+            [vec![0], vec![1, 0], vec![1, 1]]
+        );
+        assert!(panel.elements.descendant(&[1, 1]).unwrap().focusable);
+        assert!(panel.elements.descendant(&[1, 2]).is_none());
+        assert!(panel.elements.descendant(&[2, 0]).is_none());
+    }
+
+    #[test]
+    fn traversal() {
+        let mut panel = panel();
+        assert_eq!(panel.focused(), None);
+
+        // The root handles every key, so Tab never gets to move the focus.
+        assert!(panel.handle_event(tab(false)));
+        assert_eq!(panel.focused(), None);
+        panel.elements = panel.elements.with_handler(|_, _, _| false);
+
+        assert!(panel.handle_event(tab(false)));
+        assert_eq!(panel.focused(), Some([0].as_slice()));
+        assert!(panel.handle_event(tab(false)));
+        assert!(panel.handle_event(tab(false)));
+        assert_eq!(panel.focused(), Some([1, 1].as_slice()));
+        assert!(panel.handle_event(tab(false)));
+        assert_eq!(panel.focused(), Some([0].as_slice()));
+        assert!(panel.handle_event(tab(true)));
+        assert_eq!(panel.focused(), Some([1, 1].as_slice()));
+        assert!(panel.elements.descendant(&[1, 1]).unwrap().is_focused());
+        assert!(!panel.elements.descendant(&[0]).unwrap().is_focused());
+
+        panel.blur();
+        assert_eq!(panel.focused(), None);
+        assert!(!panel.elements.descendant(&[1, 1]).unwrap().is_focused());
+        assert!(panel.handle_event(tab(true)));
+        assert_eq!(panel.focused(), Some([1, 1].as_slice()));
+    }
+
+    #[test]
+    fn text_goes_to_the_focused_element() {
+        let mut panel = panel();
+        assert!(!panel.focus(&[2]));
+        assert!(!panel.focus(&[3]));
+        assert!(panel.focus(&[1, 0]));
+
+        assert!(panel.handle_event(Event::Text('1')));
+        // Letters are not handled by the field, so they bubble up to the root.
+        assert!(panel.handle_event(Event::Text('a')));
+        assert_eq!(panel.data_mut().as_slice(), [2, 2, 0]);
+    }
+
+    #[test]
+    fn pointer_press_moves_focus() {
+        let mut panel = panel();
+        let press = |y| Event::PointerPress {
+            x: 1,
+            y,
+            button: Button::Left,
+        };
+        let line = panel.elements.style.font.height() as u32;
+
+        assert!(panel.handle_event(press(1)));
+        assert_eq!(panel.focused(), Some([0].as_slice()));
+        // The last field is not focusable, so the focus is dropped.
+        assert!(panel.handle_event(press(2 * line + 1)));
+        assert_eq!(panel.focused(), None);
+        assert!(panel.handle_event(press(line + 1)));
+        assert_eq!(panel.focused(), Some([1, 0].as_slice()));
+    }
+
+    #[test]
+    fn focus_style() {
+        let mut panel = panel();
+        let field = panel.elements.descendant_mut(&[1, 1]).unwrap();
+        field.style.background = RED;
+        assert_eq!(field.colors(), ([0, 0, 0, 0xff], RED));
+        panel.focus(&[1, 1]);
+        let field = panel.elements.descendant(&[1, 1]).unwrap();
+        assert_eq!(field.colors(), (RED, [0, 0, 0, 0xff]));
+        // Without a focus style, focus does not change the colors.
+        panel.focus(&[0]);
+        let field = panel.elements.descendant(&[0]).unwrap();
+        assert_eq!(
+            field.colors(),
+            (field.style.foreground, field.style.background)
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::ops::Range;
+
+use super::layout::{split_spare, Shares};
+use super::{Constraints, Dimensions, Element};
+use crate::error::LayoutError;
+
+/// The size of a column or row of a [`Grid`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Track {
+    /// Exactly this many pixels, whatever the size of the cells in the track.
+    Fixed(u32),
+    /// As large as the largest cell in the track.
+    #[default]
+    Fit,
+    /// As large as the largest cell in the track, plus a share of the room that is left over in
+    /// the grid. The room is shared among all `Flex` tracks in proportion to their weights.
+    Flex(u32),
+}
+
+/// The cells that an [`Element`] covers when it is a child of a [`Grid`].
+///
+/// A span of zero is treated as a span of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridCell {
+    pub column: u32,
+    pub row: u32,
+    pub column_span: u32,
+    pub row_span: u32,
+}
+
+impl GridCell {
+    /// Creates a new [`GridCell`] that covers the single cell at `column` and `row`.
+    pub fn new(column: u32, row: u32) -> Self {
+        Self {
+            column,
+            row,
+            column_span: 1,
+            row_span: 1,
+        }
+    }
+
+    pub fn with_span(mut self, columns: u32, rows: u32) -> Self {
+        self.column_span = columns;
+        self.row_span = rows;
+        self
+    }
+
+    /// Returns the indices of the columns this [`GridCell`] covers.
+    fn columns(&self) -> Range<usize> {
+        let start = self.column as usize;
+        start..start + self.column_span.max(1) as usize
+    }
+
+    /// Returns the indices of the rows this [`GridCell`] covers.
+    fn rows(&self) -> Range<usize> {
+        let start = self.row as usize;
+        start..start + self.row_span.max(1) as usize
+    }
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+/// Children laid out in columns and rows, where every child is placed in the cells given by its
+/// [`GridCell`], see [`Element::with_cell`].
+///
+/// Unlike a [`Content::Stack`](super::Content::Stack) of [`Content::Row`](super::Content::Row)s,
+/// all cells in a column share the same width, so that the columns line up. Columns and rows
+/// beyond the `columns` and `rows` that are defined are [`Track::Fit`].
+///
+/// Within its cells, a child is positioned according to its [`Flex`](super::Flex): the flexed
+/// sides take up the room that the child leaves in its cells, split by their weights.
+pub struct Grid<D> {
+    pub columns: Vec<Track>,
+    pub rows: Vec<Track>,
+    pub children: Vec<Element<D>>,
+    /// The start of every column within the fill area, followed by the end of the last column.
+    column_offsets: Vec<u32>,
+    /// The start of every row within the fill area, followed by the end of the last row.
+    row_offsets: Vec<u32>,
+}
+
+impl<D> Grid<D> {
+    /// Creates a new [`Grid`].
+    pub fn new(columns: Vec<Track>, rows: Vec<Track>, children: Vec<Element<D>>) -> Self {
+        Self {
+            columns,
+            rows,
+            children,
+            column_offsets: Vec::new(),
+            row_offsets: Vec::new(),
+        }
+    }
+
+    /// Lay out the children within the `room` of the grid and work out the size of every track
+    /// from them.
+    ///
+    /// Returns the size that the tracks take up together, before any room is shared among the
+    /// [`Track::Flex`] tracks by [`Grid::arrange`].
+    pub(super) fn measure(&mut self, room: Constraints) -> Result<Dimensions, LayoutError> {
+        for (idx, child) in self.children.iter_mut().enumerate() {
+            child.layout(room).map_err(|err| err.within(idx))?;
+        }
+        let cells = self
+            .children
+            .iter()
+            .map(|child| (child.cell, child.overall_size()));
+        fit_tracks(
+            &self.columns,
+            cells
+                .clone()
+                .map(|(cell, size)| (cell.columns(), size.width)),
+            &mut self.column_offsets,
+        );
+        fit_tracks(
+            &self.rows,
+            cells.map(|(cell, size)| (cell.rows(), size.height)),
+            &mut self.row_offsets,
+        );
+        Ok(Dimensions {
+            width: self.column_offsets.iter().sum(),
+            height: self.row_offsets.iter().sum(),
+        })
+    }
+
+    /// Share the room that is left within the `fill` size among the [`Track::Flex`] tracks, work
+    /// out where each track starts, and place the children in their cells.
+    ///
+    /// Returns the size that the tracks take up together. Must be called after
+    /// [`Grid::measure`].
+    pub(super) fn arrange(&mut self, fill: Dimensions) -> Dimensions {
+        flex_tracks(&self.columns, &mut self.column_offsets, fill.width);
+        flex_tracks(&self.rows, &mut self.row_offsets, fill.height);
+        for child in &mut self.children {
+            child.position = child.place_in_cell(&self.column_offsets, &self.row_offsets);
+        }
+        let end = |offsets: &[u32]| offsets.last().copied().unwrap_or_default();
+        Dimensions::new(end(&self.column_offsets), end(&self.row_offsets))
+    }
+}
+
+impl<D> Element<D> {
+    /// Returns the position of this [`Element`] within its cells of a [`Grid`] with these track
+    /// offsets.
+    fn place_in_cell(&self, column_offsets: &[u32], row_offsets: &[u32]) -> (i64, i64) {
+        let size = self.overall_size();
+        let (x, width) = span(column_offsets, self.cell.columns());
+        let (y, height) = span(row_offsets, self.cell.rows());
+        let align = |start: u32, room: u32, size: u32, before: u32, after: u32| {
+            (start + split_spare(room.saturating_sub(size), before, after)) as i64
+        };
+        (
+            align(x, width, size.width, self.flex.left, self.flex.right),
+            align(y, height, size.height, self.flex.top, self.flex.bottom),
+        )
+    }
+}
+
+/// Returns the definition of the track at `idx`.
+fn track(tracks: &[Track], idx: usize) -> Track {
+    tracks.get(idx).copied().unwrap_or_default()
+}
+
+/// Returns the start and length of the tracks in `range`, given the `offsets` of the tracks.
+fn span(offsets: &[u32], range: Range<usize>) -> (u32, u32) {
+    let last = offsets.len().saturating_sub(1);
+    let start = offsets
+        .get(range.start.min(last))
+        .copied()
+        .unwrap_or_default();
+    let end = offsets
+        .get(range.end.min(last))
+        .copied()
+        .unwrap_or_default();
+    (start, end - start)
+}
+
+/// Work out the size of every track from the `cells` that lie in them, and store them in `sizes`.
+///
+/// Each cell is given as the range of tracks it covers and its size along them. Cells that cover a
+/// single track are fitted first. If a cell that spans several tracks does not fit into them, the
+/// room it lacks is shared among the tracks that are not [`Track::Fixed`].
+fn fit_tracks(
+    tracks: &[Track],
+    cells: impl Iterator<Item = (Range<usize>, u32)> + Clone,
+    sizes: &mut Vec<u32>,
+) {
+    let count = cells
+        .clone()
+        .map(|(range, _)| range.end)
+        .max()
+        .unwrap_or_default()
+        .max(tracks.len());
+    sizes.clear();
+    sizes.extend((0..count).map(|idx| match track(tracks, idx) {
+        Track::Fixed(size) => size,
+        Track::Fit | Track::Flex(_) => 0,
+    }));
+    let grows = |idx: &usize| !matches!(track(tracks, *idx), Track::Fixed(_));
+
+    for (range, size) in cells.clone().filter(|(range, _)| range.len() == 1) {
+        if grows(&range.start) {
+            sizes[range.start] = sizes[range.start].max(size);
+        }
+    }
+    for (range, size) in cells.filter(|(range, _)| range.len() > 1) {
+        let lacking = size.saturating_sub(sizes[range.clone()].iter().sum());
+        let growing = range.clone().filter(grows).count() as u32;
+        if lacking == 0 || growing == 0 {
+            continue;
+        }
+        for (n, idx) in range.filter(grows).enumerate() {
+            // Spread the remainder over the first tracks, so that no pixel is lost.
+            let extra = (n as u32) < lacking % growing;
+            sizes[idx] += lacking / growing + extra as u32;
+        }
+    }
+}
+
+/// Share the room beyond the `sizes` of the tracks within `room` among the [`Track::Flex`] tracks,
+/// and then turn the `sizes` into offsets, followed by the end of the last track.
+fn flex_tracks(tracks: &[Track], sizes: &mut Vec<u32>, room: u32) {
+    let spare = room.saturating_sub(sizes.iter().sum());
+    let weight = |idx: usize| match track(tracks, idx) {
+        Track::Flex(weight) => weight,
+        Track::Fixed(_) | Track::Fit => 0,
+    };
+    let total = (0..sizes.len()).map(|idx| weight(idx) as u64).sum();
+    let mut shares = Shares::new(spare, total);
+    for (idx, size) in sizes.iter_mut().enumerate() {
+        *size += shares.next(weight(idx));
+    }
+
+    let mut offset = 0;
+    for size in sizes.iter_mut() {
+        let start = offset;
+        offset += *size;
+        *size = start;
+    }
+    sizes.push(offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(tracks: &[Track], cells: &[(Range<usize>, u32)], room: u32) -> Vec<u32> {
+        let mut sizes = Vec::new();
+        fit_tracks(tracks, cells.iter().cloned(), &mut sizes);
+        flex_tracks(tracks, &mut sizes, room);
+        sizes
+    }
+
+    #[test]
+    fn fit_and_fixed() {
+        let tracks = [Track::Fit, Track::Fixed(10)];
+        let cells = [(0..1, 30), (0..1, 20), (1..2, 50), (2..3, 5)];
+        // The third track is not defined, so it fits its cell.
+        assert_eq!(offsets(&tracks, &cells, 0), [0, 30, 40, 45]);
+        assert_eq!(offsets(&[], &[], 100), [0]);
+    }
+
+    #[test]
+    fn spanning_cells() {
+        let tracks = [Track::Fixed(10), Track::Fit, Track::Fit];
+        // The 25 pixels that the spanning cell lacks go to the tracks that can grow.
+        let cells = [(0..3, 40), (1..2, 5)];
+        assert_eq!(offsets(&tracks, &cells, 0), [0, 10, 28, 40]);
+        // A cell that fits already leaves the tracks alone.
+        let cells = [(0..2, 12), (1..2, 5)];
+        assert_eq!(offsets(&tracks, &cells, 0), [0, 10, 15, 15]);
+    }
+
+    #[test]
+    fn flex_shares() {
+        let tracks = [Track::Flex(2), Track::Fit, Track::Flex(1)];
+        let cells = [(0..1, 10), (1..2, 10)];
+        // 80 spare pixels, split 2:1 without losing the remainder.
+        assert_eq!(offsets(&tracks, &cells, 100), [0, 63, 73, 100]);
+        // Without room to spare, flex tracks simply fit their cells.
+        assert_eq!(offsets(&tracks, &cells, 10), [0, 10, 20, 20]);
+    }
+}
@@ -0,0 +1,248 @@
+use super::{Dimensions, Element};
+
+/// The result of a hit test, see [`Element::hit_test`] and [`Panel::hit_test`](crate::Panel::hit_test).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    /// The indices of the children that lead from the root [`Element`] to the hit `Element`.
+    ///
+    /// An empty path refers to the root itself.
+    pub path: Vec<usize>,
+    /// The horizontal position relative to the top left corner of the hit [`Element`], including
+    /// its padding.
+    pub x: u32,
+    /// The vertical position relative to the top left corner of the hit [`Element`], including
+    /// its padding.
+    pub y: u32,
+}
+
+impl<D> Element<D> {
+    /// Find the deepest [`Element`] that lies under the position `x`, `y`.
+    ///
+    /// The position is relative to the top left corner of this `Element`, including its padding.
+    /// The padding of an `Element` belongs to it, so a position within the padding of a child hits
+    /// that child. Children are clipped to the fill area of their parent, just like they are when
+    /// they are drawn, and the scroll of every `Element` is taken into account. Of overlapping
+    /// children, such as those of a [`Content::Layers`](super::Content::Layers), the one drawn
+    /// last is hit. The bars of a [`Scrollbar`](super::Scrollbar) belong to the `Element` they are
+    /// drawn on.
+    ///
+    /// Returns [`None`] if the position lies outside of this `Element`.
+    pub fn hit_test(&self, x: u32, y: u32) -> Option<Hit> {
+        let Dimensions { width, height } = self.overall_size();
+        if x >= width || y >= height {
+            return None;
+        }
+
+        let mut hit = Hit {
+            path: Vec::new(),
+            x,
+            y,
+        };
+        self.hit_test_children(&mut hit);
+        Some(hit)
+    }
+
+    /// Descend into the children of this [`Element`] for as long as they contain the position of
+    /// the `hit`, updating it along the way.
+    fn hit_test_children(&self, hit: &mut Hit) {
+        let children = self.content.children();
+        if children.is_empty() {
+            return;
+        }
+
+        // Only the fill area of the parent is drawn onto, so the children are clipped to it.
+        let fill = self.fill_size();
+        let (Some(x), Some(y)) = (
+            hit.x.checked_sub(self.padding.left),
+            hit.y.checked_sub(self.padding.top),
+        ) else {
+            return;
+        };
+        // The bars of a scrollbar lie on top of the children.
+        if x >= fill.width || y >= fill.height || self.on_scrollbar(x, y) {
+            return;
+        }
+        let (scroll_x, scroll_y) = self.scroll_offset();
+        let (x, y) = ((x + scroll_x) as i64, (y + scroll_y) as i64);
+
+        // Children that are drawn later end up on top, so we look at them first.
+        let found = children.iter().enumerate().rev().find_map(|(idx, child)| {
+            let (cx, cy) = child.position;
+            let Dimensions { width, height } = child.overall_size();
+            let (lx, ly) = (u32::try_from(x - cx).ok()?, u32::try_from(y - cy).ok()?);
+            (lx < width && ly < height).then_some((idx, child, lx, ly))
+        });
+
+        if let Some((idx, child, lx, ly)) = found {
+            hit.path.push(idx);
+            hit.x = lx;
+            hit.y = ly;
+            child.hit_test_children(hit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use fleck::Font;
+
+    use super::*;
+    use crate::elements::{Alignment, Anchor, AnchorPoint, Constraints, Content};
+    use crate::testing::font;
+
+    fn text(font: &Rc<Font>, s: &str) -> Element<()> {
+        Element::still(
+            Rc::clone(font),
+            Content::Text(s.to_string(), Alignment::Left),
+        )
+    }
+
+    #[test]
+    fn row_with_flex_and_padding() {
+        let font = font();
+        let mut row = Element::still(
+            Rc::clone(&font),
+            Content::Row(vec![
+                text(&font, "left")
+                    .with_padding_left(4)
+                    .with_flex_right(true),
+                text(&font, "right").with_minheight(32),
+            ]),
+        )
+        .with_padding_left(10)
+        .with_padding_top(5)
+        .with_fixedwidth(200);
+        row.layout(Constraints::default()).unwrap();
+        let Content::Row(children) = &row.content else {
+            unreachable!()
+        };
+        let right_width = children[1].overall_size().width;
+
+        // The padding of the row itself.
+        assert_eq!(
+            row.hit_test(3, 3),
+            Some(Hit {
+                path: vec![],
+                x: 3,
+                y: 3
+            })
+        );
+        // The padding of the first child belongs to that child.
+        assert_eq!(
+            row.hit_test(12, 6),
+            Some(Hit {
+                path: vec![0],
+                x: 2,
+                y: 1
+            })
+        );
+        // Below the first child, there is only the row.
+        assert_eq!(row.hit_test(12, 30).map(|hit| hit.path), Some(vec![]));
+        // The flex room pushes the second child all the way to the right.
+        let start = 10 + 200 - right_width;
+        assert_eq!(
+            row.hit_test(start, 36),
+            Some(Hit {
+                path: vec![1],
+                x: 0,
+                y: 31
+            })
+        );
+        assert_eq!(
+            row.hit_test(start - 1, 36).map(|hit| hit.path),
+            Some(vec![])
+        );
+        assert_eq!(row.hit_test(210, 0), None);
+    }
+
+    #[test]
+    fn scrolled_stack() {
+        let font = font();
+        let mut stack = Element::still(
+            Rc::clone(&font),
+            Content::Stack((0..8).map(|i| text(&font, &format!("line {i}"))).collect()),
+        )
+        .with_fixedheight(48)
+        .with_scroll(24);
+        stack.layout(Constraints::default()).unwrap();
+
+        assert_eq!(
+            stack.hit_test(1, 0),
+            Some(Hit {
+                path: vec![1],
+                x: 1,
+                y: 8
+            })
+        );
+        assert_eq!(stack.hit_test(1, 47).map(|hit| hit.path), Some(vec![4]));
+        assert_eq!(stack.hit_test(1, 48), None);
+    }
+
+    #[test]
+    fn overlapping_layers() {
+        let font = font();
+        let mut layers = Element::still(
+            Rc::clone(&font),
+            Content::Layers(vec![
+                text(&font, "underneath").with_fixedwidth(100),
+                text(&font, "popup")
+                    .with_anchor(Anchor::new(AnchorPoint::TopRight).with_offset(-10, 0)),
+            ]),
+        );
+        layers.layout(Constraints::default()).unwrap();
+        let Content::Layers(children) = &layers.content else {
+            unreachable!()
+        };
+        let popup_width = children[1].overall_size().width;
+        let start = 100 - 10 - popup_width;
+
+        assert_eq!(
+            layers.hit_test(start - 1, 2).map(|hit| hit.path),
+            Some(vec![0])
+        );
+        // The popup is drawn last, so it is on top.
+        assert_eq!(
+            layers.hit_test(start, 2),
+            Some(Hit {
+                path: vec![1],
+                x: 0,
+                y: 2
+            })
+        );
+        assert_eq!(layers.hit_test(95, 2).map(|hit| hit.path), Some(vec![0]));
+    }
+
+    #[test]
+    fn scrolled_row() {
+        let font = font();
+        let mut row = Element::still(
+            Rc::clone(&font),
+            Content::Row(vec![
+                text(&font, "left").with_fixedwidth(50),
+                text(&font, "right").with_fixedwidth(50),
+            ]),
+        )
+        .with_fixedwidth(60)
+        .with_scroll_x(30);
+        row.layout(Constraints::default()).unwrap();
+
+        assert_eq!(
+            row.hit_test(10, 4),
+            Some(Hit {
+                path: vec![0],
+                x: 40,
+                y: 4
+            })
+        );
+        assert_eq!(
+            row.hit_test(20, 4),
+            Some(Hit {
+                path: vec![1],
+                x: 0,
+                y: 4
+            })
+        );
+    }
+}
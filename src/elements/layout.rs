@@ -0,0 +1,805 @@
+use super::{
+    Arrangement, Content, CrossAlign, Dimensions, Element, Flex, Justify, Padding, Size,
+    SizingStrategy,
+};
+use crate::error::LayoutError;
+
+/// The room that a parent offers to a child [`Element`] when it is laid out.
+///
+/// The constraints apply to the overall size of the child, so including its padding. They are
+/// combined with the [`Size`] of the child itself, where the maximum wins when the two contradict
+/// each other. An unset maximum leaves the child free to grow along that axis.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Constraints {
+    pub minwidth: u32,
+    pub minheight: u32,
+    pub maxwidth: Option<u32>,
+    pub maxheight: Option<u32>,
+}
+
+impl Constraints {
+    /// Creates new [`Constraints`] that allow any size up to `maxwidth` by `maxheight`.
+    pub fn new(maxwidth: Option<u32>, maxheight: Option<u32>) -> Self {
+        Self {
+            minwidth: 0,
+            minheight: 0,
+            maxwidth,
+            maxheight,
+        }
+    }
+
+    pub fn with_maxwidth(mut self, maxwidth: u32) -> Self {
+        self.maxwidth = Some(maxwidth);
+        self
+    }
+
+    pub fn with_maxheight(mut self, maxheight: u32) -> Self {
+        self.maxheight = Some(maxheight);
+        self
+    }
+
+    pub fn with_minwidth(mut self, minwidth: u32) -> Self {
+        self.minwidth = minwidth;
+        self
+    }
+
+    pub fn with_minheight(mut self, minheight: u32) -> Self {
+        self.minheight = minheight;
+        self
+    }
+
+    /// Returns these [`Constraints`] with the room that `padding` takes up removed.
+    fn without_padding(self, padding: Padding) -> Self {
+        let horizontal = padding.left + padding.right;
+        let vertical = padding.top + padding.bottom;
+        Self {
+            minwidth: self.minwidth.saturating_sub(horizontal),
+            minheight: self.minheight.saturating_sub(vertical),
+            maxwidth: self.maxwidth.map(|w| w.saturating_sub(horizontal)),
+            maxheight: self.maxheight.map(|h| h.saturating_sub(vertical)),
+        }
+    }
+
+    /// Returns these [`Constraints`] narrowed down by the `min` and `max` dimensions of `size`.
+    fn within(self, size: Size) -> Self {
+        let narrow = |outer: Option<u32>, own: Option<u32>| match (outer, own) {
+            (Some(outer), Some(own)) => Some(outer.min(own)),
+            (outer, own) => outer.or(own),
+        };
+        let maxwidth = narrow(self.maxwidth, size.maxwidth);
+        let maxheight = narrow(self.maxheight, size.maxheight);
+        let minwidth = self.minwidth.max(size.minwidth.unwrap_or_default());
+        let minheight = self.minheight.max(size.minheight.unwrap_or_default());
+        Self {
+            minwidth: maxwidth.map_or(minwidth, |max| minwidth.min(max)),
+            minheight: maxheight.map_or(minheight, |max| minheight.min(max)),
+            maxwidth,
+            maxheight,
+        }
+    }
+}
+
+impl<D> Element<D> {
+    /// Lay out this [`Element`] and its children within the `constraints` offered by its parent.
+    ///
+    /// This happens in two steps. First, the children are measured within the constraints that
+    /// this `Element` passes on to them, after which this `Element` settles on its own size
+    /// according to its [`SizingStrategy`]. Then the children are arranged within that size, and
+    /// their positions are stored, so that drawing, hit testing and dispatching events all work
+    /// from the same geometry. For container [`Content`]s, this method is called recursively.
+    ///
+    /// After calling this method, the `Element`'s width and height as accessed through
+    /// [`Element::fill_size`] or [`Element::overall_size`] are reliably defined and are valid
+    /// until the content of it or any of its children are mutated.
+    ///
+    /// In case of an `Element` with content [`Content::Paragraph`] or [`Content::Editor`], the
+    /// inner text is wrapped to the maximum width, and the `width` and `height` are subsequently
+    /// calculated based on these wrapped lines.
+    ///
+    /// The `scroll` and `scroll_x` of every `Element` are clamped to its [`Element::max_scroll`].
+    ///
+    /// # Errors
+    ///
+    /// If the constraints of this `Element` or any of its children contradict each other, a
+    /// [`LayoutError`] is returned. The `Element`s that were laid out before the error was
+    /// encountered are updated, the rest is left untouched.
+    pub(crate) fn layout(&mut self, constraints: Constraints) -> Result<(), LayoutError> {
+        let Size {
+            minwidth,
+            maxwidth,
+            minheight,
+            maxheight,
+            ..
+        } = self.size();
+        if let (Some(minwidth), Some(maxwidth)) = (minwidth, maxwidth) {
+            if minwidth > maxwidth {
+                return Err(LayoutError::InvalidWidth {
+                    path: Vec::new(),
+                    minwidth,
+                    maxwidth,
+                });
+            }
+        }
+        if let (Some(minheight), Some(maxheight)) = (minheight, maxheight) {
+            if minheight > maxheight {
+                return Err(LayoutError::InvalidHeight {
+                    path: Vec::new(),
+                    minheight,
+                    maxheight,
+                });
+            }
+        }
+
+        let fill = constraints.without_padding(self.padding).within(self.size);
+        let natural = self.measure(fill)?;
+        self.size.baked_width = self.resolve(natural.width, fill.minwidth, fill.maxwidth, minwidth);
+        self.size.baked_height =
+            self.resolve(natural.height, fill.minheight, fill.maxheight, minheight);
+        self.content_size = self.arrange(natural)?;
+
+        // The content may have shrunk or the fill area grown since the scroll was set, so it is
+        // brought back within range, where drawing expects it to be.
+        let max = self.max_scroll();
+        self.scroll_x = self.scroll_x.map(|scroll| scroll.min(max.width));
+        if !matches!(self.content, Content::Table(..)) {
+            self.scroll = self.scroll.map(|scroll| scroll.min(max.height));
+        }
+        Ok(())
+    }
+
+    /// Returns the natural size of the content of this [`Element`] within the `fill` constraints,
+    /// laying out its children along the way.
+    fn measure(&mut self, fill: Constraints) -> Result<Dimensions, LayoutError> {
+        let font = &self.style.font;
+        let gap = self.arrangement.gap;
+        let gaps = |children: usize| gap * children.saturating_sub(1) as u32;
+        let (width, height);
+        match &mut self.content {
+            Content::Text(text, _) => {
+                width = font.determine_width(text) as u32;
+                height = font.height() as u32;
+            }
+            Content::Paragraph(wrapped, _) => {
+                wrapped.rewrap(fill.maxwidth, font);
+                width = wrapped
+                    .lines()
+                    .map(|line| font.determine_width(line) as u32)
+                    .max()
+                    .unwrap_or_default();
+                height = (font.height() * wrapped.lines_count()) as u32;
+            }
+            Content::Custom { buf, height: h } => {
+                let len = buf.len();
+                if (*h == 0 && len != 0) || (*h != 0 && len % *h as usize != 0) {
+                    return Err(LayoutError::InvalidCustom {
+                        path: Vec::new(),
+                        len,
+                        height: *h,
+                    });
+                }
+                width = (len as u32).checked_div(*h).unwrap_or_default();
+                height = *h;
+            }
+            Content::Input(input, _) => {
+                width = input.natural_width(font);
+                height = font.height() as u32;
+            }
+            Content::Editor(editor, _) => {
+                // Leave a pixel of room for the cursor behind the longest line.
+                editor.rewrap(fill.maxwidth.map(|width| width.saturating_sub(1)), font);
+                let wrapped = editor.wrapped();
+                width = wrapped
+                    .lines()
+                    .map(|line| font.determine_width(line) as u32 + 1)
+                    .max()
+                    .unwrap_or_default();
+                height = (font.height() * wrapped.lines_count()) as u32;
+            }
+            Content::Table(table, _) => {
+                Dimensions { width, height } = table.measure(font);
+            }
+            Content::Row(children) => {
+                // Children that do not fit next to each other are shrunk once they are arranged.
+                let room = Constraints::new(fill.maxwidth, fill.maxheight);
+                let (mut sum, mut max) = (0, 0);
+                for (idx, child) in children.iter_mut().enumerate() {
+                    child.layout(room).map_err(|err| err.within(idx))?;
+                    let size = child.overall_size();
+                    sum += size.width;
+                    max = max.max(size.height);
+                }
+                (width, height) = (sum + gaps(children.len()), max);
+            }
+            Content::Stack(children) => {
+                // A stack can scroll through its children, so they are not limited in height.
+                let room = Constraints::new(fill.maxwidth, None);
+                let (mut sum, mut max) = (0, 0);
+                for (idx, child) in children.iter_mut().enumerate() {
+                    child.layout(room).map_err(|err| err.within(idx))?;
+                    let size = child.overall_size();
+                    sum += size.height;
+                    max = max.max(size.width);
+                }
+                (width, height) = (max, sum + gaps(children.len()));
+            }
+            Content::Layers(children) => {
+                let room = Constraints::new(fill.maxwidth, fill.maxheight);
+                let mut max = Dimensions::default();
+                for (idx, child) in children.iter_mut().enumerate() {
+                    child.layout(room).map_err(|err| err.within(idx))?;
+                    let size = child.overall_size();
+                    max.width = max.width.max(size.width);
+                    max.height = max.height.max(size.height);
+                }
+                Dimensions { width, height } = max;
+            }
+            Content::Grid(grid) => {
+                let room = Constraints::new(fill.maxwidth, fill.maxheight);
+                Dimensions { width, height } = grid.measure(room)?;
+            }
+            // Like a stack, a list scrolls through its rows, but only those in view are laid out.
+            Content::VirtualList(list) => {
+                Dimensions { width, height } = list.measure(fill.maxwidth)?;
+            }
+        }
+        Ok(Dimensions { width, height })
+    }
+
+    /// Returns the size along one axis that this [`Element`] settles on, given the `natural` size
+    /// of its content and the combined `min` and `max` of its parent and itself.
+    fn resolve(&self, natural: u32, min: u32, max: Option<u32>, own_min: Option<u32>) -> u32 {
+        let preferred = match self.size.strategy {
+            SizingStrategy::Whatever => natural,
+            SizingStrategy::Chonker => max.map_or(natural, |max| max.max(natural)),
+            // Without any minimum to shrink down to, the natural size is as small as it gets.
+            SizingStrategy::Smollest if own_min.is_none() && min == 0 => natural,
+            SizingStrategy::Smollest => min,
+        };
+        // The minimum was already capped by the maximum, so the maximum wins.
+        let preferred = preferred.max(min);
+        max.map_or(preferred, |max| preferred.min(max))
+    }
+
+    /// Place the children of this [`Element`] within its fill size, and store their positions.
+    ///
+    /// Returns the size of the area that the content takes up, which is the `natural` size for
+    /// content without children. For a [`Content::Row`] or [`Content::Stack`], this includes the
+    /// room that is taken up by the flexes of the children.
+    ///
+    /// Children that grow or shrink are laid out once more at their new size, which can fail.
+    fn arrange(&mut self, natural: Dimensions) -> Result<Dimensions, LayoutError> {
+        let fill = self.fill_size();
+        let size = match &mut self.content {
+            Content::Input(input, _) => {
+                input.scroll_into_view(fill.width, &self.style.font);
+                natural
+            }
+            Content::Row(children) => {
+                arrange_flow(children, fill, Axis::Horizontal, self.arrangement)?
+            }
+            Content::Stack(children) => {
+                arrange_flow(children, fill, Axis::Vertical, self.arrangement)?
+            }
+            Content::Layers(children) => {
+                for child in children.iter_mut() {
+                    child.position = child.anchor.position(fill, child.overall_size());
+                }
+                natural
+            }
+            Content::Grid(grid) => grid.arrange(fill),
+            Content::VirtualList(list) => {
+                list.arrange();
+                natural
+            }
+            Content::Text(..)
+            | Content::Paragraph(..)
+            | Content::Custom { .. }
+            | Content::Editor(..)
+            | Content::Table(..) => natural,
+        };
+        Ok(size)
+    }
+
+    /// Returns the size of the area that the content of this [`Element`] takes up, as it was
+    /// laid out.
+    ///
+    /// For a container, this is the area that its children are arranged in, which may be larger
+    /// or smaller than its [`Element::fill_size`]. For other kinds of [`Content`], it is the
+    /// natural size of the content, such as the size of all lines of a [`Content::Paragraph`].
+    pub fn content_size(&self) -> Dimensions {
+        self.content_size
+    }
+
+    /// Returns how far the content of this [`Element`] can be scrolled to the right and down
+    /// before it runs out, as it was laid out.
+    ///
+    /// This is how much the [`Element::content_size`] exceeds the fill size, through which the
+    /// content is seen. Scrolling any further than this leaves part of the fill area empty. For a
+    /// [`Content::Table`], the vertical scroll counts rows, so the `height` is meaningless.
+    pub fn max_scroll(&self) -> Dimensions {
+        let fill = self.fill_size();
+        Dimensions {
+            width: self.content_size.width.saturating_sub(fill.width),
+            height: self.content_size.height.saturating_sub(fill.height),
+        }
+    }
+
+    /// Returns the distance from the top of the content to the wrapped `line` of a
+    /// [`Content::Paragraph`] or [`Content::Editor`], which is how far the [`Element`] is
+    /// scrolled to bring that line to the top.
+    ///
+    /// Returns [`None`] for other kinds of [`Content`], or if there is no such `line`.
+    pub fn line_offset(&self, line: usize) -> Option<u32> {
+        let wrapped = match &self.content {
+            Content::Paragraph(wrapped, _) => wrapped,
+            Content::Editor(editor, _) => editor.wrapped(),
+            _ => return None,
+        };
+        (line < wrapped.lines_count()).then(|| (line * self.style.font.height()) as u32)
+    }
+
+    /// Returns the position of this [`Element`] within the fill area of its parent, as it was
+    /// laid out.
+    ///
+    /// The position is the top left corner of the overall area of this `Element`, so including
+    /// its padding. The `scroll` of the parent is not taken into account. The children of a
+    /// [`Content::Layers`] may be anchored outside of the fill area, so a position can be
+    /// negative.
+    pub fn position(&self) -> (i64, i64) {
+        self.position
+    }
+}
+
+/// The direction along which a [`Content::Row`] or [`Content::Stack`] lines up its children, or
+/// along which a [`Scrollbar`](super::Scrollbar) runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    /// Returns the length of `size` along this [`Axis`], followed by its length across it.
+    pub(super) fn split(self, size: Dimensions) -> (u32, u32) {
+        match self {
+            Axis::Horizontal => (size.width, size.height),
+            Axis::Vertical => (size.height, size.width),
+        }
+    }
+
+    /// Returns the [`Dimensions`] that are `along` this [`Axis`] and `across` it.
+    fn join(self, along: u32, across: u32) -> Dimensions {
+        match self {
+            Axis::Horizontal => Dimensions::new(along, across),
+            Axis::Vertical => Dimensions::new(across, along),
+        }
+    }
+
+    /// Returns the weights of the sides of `flex` before and after along this [`Axis`], followed
+    /// by those before and after across it.
+    fn sides(self, flex: Flex) -> ((u32, u32), (u32, u32)) {
+        let horizontal = (flex.left, flex.right);
+        let vertical = (flex.top, flex.bottom);
+        match self {
+            Axis::Horizontal => (horizontal, vertical),
+            Axis::Vertical => (vertical, horizontal),
+        }
+    }
+
+    /// Returns [`Constraints`] that allow a length from `min` to `max` along this [`Axis`], and
+    /// from `cross_min` to `cross_max` across it.
+    fn constraints(self, min: u32, max: u32, cross_min: u32, cross_max: u32) -> Constraints {
+        let (width, height) = match self {
+            Axis::Horizontal => ((min, max), (cross_min, cross_max)),
+            Axis::Vertical => ((cross_min, cross_max), (min, max)),
+        };
+        Constraints::new(Some(width.1), Some(height.1))
+            .with_minwidth(width.0)
+            .with_minheight(height.0)
+    }
+}
+
+/// Hands out some room in proportion to weights, one share at a time.
+///
+/// The shares are worked out from the running total of the weights, so that they add up to exactly
+/// the room once all weights have been handed a share, without losing any remainder.
+pub(super) struct Shares {
+    room: u64,
+    total: u64,
+    before: u64,
+}
+
+impl Shares {
+    /// Creates new [`Shares`] of `room` among weights that add up to `total`.
+    pub(super) fn new(room: u32, total: u64) -> Self {
+        Self {
+            room: room as u64,
+            total,
+            before: 0,
+        }
+    }
+
+    /// Returns the share for the next `weight`.
+    pub(super) fn next(&mut self, weight: u32) -> u32 {
+        // Round to the nearest pixel, where a tie goes to the later weights.
+        let half = self.total.saturating_sub(1) / 2;
+        let share = |weight: u64| {
+            (self.room * weight + half)
+                .checked_div(self.total)
+                .unwrap_or_default()
+        };
+        let after = self.before + weight as u64;
+        let next = share(after) - share(self.before);
+        self.before = after;
+        next as u32
+    }
+}
+
+/// Returns the part of the `spare` room that goes before a child, when it is split between the
+/// sides `before` and `after` it by their weights.
+pub(super) fn split_spare(spare: u32, before: u32, after: u32) -> u32 {
+    Shares::new(spare, before as u64 + after as u64).next(before)
+}
+
+/// Lay out `children` one after another along `axis` within `fill`, growing, shrinking or
+/// stretching them as their [`Flex`] and the `arrangement` ask, and store their positions.
+///
+/// Returns the size of the area that the children take up, including the gaps between them and
+/// the room that was handed out around them.
+fn arrange_flow<D>(
+    children: &mut [Element<D>],
+    fill: Dimensions,
+    axis: Axis,
+    arrangement: Arrangement,
+) -> Result<Dimensions, LayoutError> {
+    let Arrangement {
+        gap,
+        justify,
+        align,
+    } = arrangement;
+    let count = children.len();
+    let (main, cross) = axis.split(fill);
+    let length = |child: &Element<D>| axis.split(child.overall_size()).0;
+    let gaps = gap * count.saturating_sub(1) as u32;
+    let total = children.iter().map(length).sum::<u32>() + gaps;
+
+    // Hand the room that is left to the children that grow, or take the room that is lacking
+    // from the children that shrink. Stretched children are laid out across the full container.
+    let grows = total < main;
+    let stretch = align == CrossAlign::Stretch;
+    let weight = |child: &Element<D>| match grows {
+        true => child.flex.grow,
+        false => child.flex.shrink,
+    };
+    let weights = children.iter().map(|child| weight(child) as u64).sum();
+    let mut shares = Shares::new(main.abs_diff(total), weights);
+    for (idx, child) in children.iter_mut().enumerate() {
+        let share = shares.next(weight(child));
+        let (along, across) = axis.split(child.overall_size());
+        if share == 0 && !(stretch && across != cross) {
+            continue;
+        }
+        let (min, max) = match (share, grows) {
+            (0, _) => (along, along),
+            (share, true) => (along + share, along + share),
+            (share, false) => (0, along.saturating_sub(share)),
+        };
+        let cross_min = if stretch { cross } else { 0 };
+        let constraints = axis.constraints(min, max, cross_min, cross);
+        child.layout(constraints).map_err(|err| err.within(idx))?;
+    }
+
+    // Whatever room is still left goes to the flexed sides, and otherwise to where the children
+    // are justified.
+    let total = children.iter().map(length).sum::<u32>() + gaps;
+    let spare = main.saturating_sub(total);
+    let weights = children
+        .iter()
+        .map(|child| {
+            let ((before, after), _) = axis.sides(child.flex);
+            before as u64 + after as u64
+        })
+        .sum();
+    let mut sides = Shares::new(spare, weights);
+    let justified = |idx: usize| -> (u32, u32) {
+        let (first, last) = (idx == 0, idx + 1 == count);
+        match justify {
+            Justify::Start => (0, 0),
+            Justify::End => (first as u32, 0),
+            Justify::Center => (first as u32, last as u32),
+            Justify::SpaceBetween => (0, !last as u32),
+            Justify::SpaceAround => (1, 1),
+        }
+    };
+    let justified_weights = (0..count)
+        .map(|idx| {
+            let (before, after) = justified(idx);
+            before as u64 + after as u64
+        })
+        .sum();
+    let room = if weights == 0 { spare } else { 0 };
+    let mut justification = Shares::new(room, justified_weights);
+
+    let (mut runner, mut breadth) = (0, 0);
+    for (idx, child) in children.iter_mut().enumerate() {
+        let ((before, after), (cross_before, cross_after)) = axis.sides(child.flex);
+        let (justify_before, justify_after) = justified(idx);
+        let (along, across) = axis.split(child.overall_size());
+        runner += sides.next(before) + justification.next(justify_before);
+        let spare_across = cross.saturating_sub(across);
+        let shift = match align {
+            _ if cross_before + cross_after > 0 && !stretch => {
+                split_spare(spare_across, cross_before, cross_after)
+            }
+            CrossAlign::Start | CrossAlign::Stretch => 0,
+            CrossAlign::Center => split_spare(spare_across, 1, 1),
+            CrossAlign::End => spare_across,
+        };
+        let Dimensions { width, height } = axis.join(runner, shift);
+        child.position = (width as i64, height as i64);
+        runner += along + sides.next(after) + justification.next(justify_after);
+        if idx + 1 < count {
+            runner += gap;
+        }
+        breadth = breadth.max(across);
+    }
+    Ok(axis.join(runner, breadth))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use fleck::Font;
+
+    use super::*;
+    use crate::elements::{Alignment, WrappedText};
+    use crate::testing::font;
+
+    fn text(font: &Rc<Font>, s: &str) -> Element<()> {
+        Element::still(
+            Rc::clone(font),
+            Content::Text(s.to_string(), Alignment::Left),
+        )
+    }
+
+    #[test]
+    fn constraints_reach_children() {
+        let font = font();
+        let mut layers = Element::still(
+            Rc::clone(&font),
+            Content::Layers(vec![
+                text(&font, "grow").with_strategy(SizingStrategy::Chonker),
+                text(&font, "Hello, world."),
+            ]),
+        )
+        .with_fixedwidth(50)
+        .with_fixedheight(40);
+        layers.layout(Constraints::default()).unwrap();
+        let children = layers.content.children();
+        // A chonker grows to the room its parent offers, even without a maximum of its own.
+        assert_eq!(children[0].fill_size(), Dimensions::new(50, 40));
+        // Text that does not fit is cut off at the edge of its parent.
+        assert_eq!(children[1].fill_size(), Dimensions::new(50, 16));
+        assert_eq!(children[1].content_size(), Dimensions::new(69, 16));
+
+        let mut smol = text(&font, "Hello, world.")
+            .with_strategy(SizingStrategy::Smollest)
+            .with_padding_left(4);
+        smol.layout(Constraints::default().with_minwidth(24))
+            .unwrap();
+        assert_eq!(smol.overall_size(), Dimensions::new(24, 16));
+    }
+
+    #[test]
+    fn row_children_share_the_width() {
+        let font = font();
+        let paragraph = WrappedText::new("one two three four".to_string(), 100, &font);
+        let mut row = Element::still(
+            Rc::clone(&font),
+            Content::Row(vec![
+                text(&font, "Hello, world."),
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Paragraph(paragraph, Alignment::Left),
+                )
+                .with_shrink(1),
+            ]),
+        );
+        row.layout(Constraints::default().with_maxwidth(100))
+            .unwrap();
+        let children = row.content.children();
+        // The paragraph shrinks, and wraps within the room that the text next to it leaves.
+        assert_eq!(children[1].position(), (69, 0));
+        assert!(children[1].overall_size().width <= 31);
+        assert!(children[1].overall_size().height > 16);
+        assert_eq!(row.fill_size().height, children[1].overall_size().height);
+    }
+
+    #[test]
+    fn positions_are_stored() {
+        let font = font();
+        let mut stack = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                text(&font, "top").with_flex_bottom(true),
+                text(&font, "right").with_flex_left(true),
+            ]),
+        )
+        .with_padding_top(3)
+        .with_fixedwidth(100)
+        .with_fixedheight(60);
+        stack.layout(Constraints::default()).unwrap();
+        let children = stack.content.children();
+        let right_width = children[1].overall_size().width as i64;
+        assert_eq!(children[0].position(), (0, 0));
+        assert_eq!(children[1].position(), (100 - right_width, 44));
+        // The flex room along the stack is part of its content, the room across it is not.
+        assert_eq!(
+            stack.content_size(),
+            Dimensions::new(right_width as u32, 60)
+        );
+    }
+
+    #[test]
+    fn grow_in_proportion() {
+        let font = font();
+        let pane = |grow| {
+            Element::<()>::still(Rc::clone(&font), Content::Stack(Vec::new())).with_grow(grow)
+        };
+        let mut row = Element::still(Rc::clone(&font), Content::Row(vec![pane(2), pane(1)]))
+            .with_fixedwidth(100);
+        row.layout(Constraints::default()).unwrap();
+        let children = row.content.children();
+        assert_eq!(children[0].fill_size().width, 67);
+        assert_eq!(children[1].fill_size().width, 33);
+        assert_eq!(children[1].position(), (67, 0));
+    }
+
+    #[test]
+    fn shrink_when_overflowing() {
+        let font = font();
+        let mut row = Element::still(
+            Rc::clone(&font),
+            Content::Row(vec![
+                text(&font, "Hello, world.").with_shrink(1),
+                text(&font, "Hello, world.").with_shrink(1),
+                text(&font, "stays").with_minwidth(20),
+            ]),
+        )
+        .with_fixedwidth(70);
+        row.layout(Constraints::default()).unwrap();
+        let children = row.content.children();
+        // The room that is lacking comes out of the first two children only.
+        let stays = children[2].fill_size().width;
+        let left = 70 - stays;
+        assert_eq!(children[0].fill_size().width, left - left / 2);
+        assert_eq!(children[1].fill_size().width, left / 2);
+        assert_eq!(children[2].position(), (left as i64, 0));
+    }
+
+    #[test]
+    fn no_pixel_is_lost() {
+        let font = font();
+        let gap = || {
+            Element::<()>::still(Rc::clone(&font), Content::Stack(Vec::new())).with_flex_right(true)
+        };
+        let mut row = Element::still(Rc::clone(&font), Content::Row(vec![gap(), gap(), gap()]))
+            .with_fixedwidth(101);
+        row.layout(Constraints::default()).unwrap();
+        let positions: Vec<_> = row
+            .content
+            .children()
+            .iter()
+            .map(|c| c.position())
+            .collect();
+        assert_eq!(positions, [(0, 0), (34, 0), (67, 0)]);
+        assert_eq!(row.content_size().width, 101);
+    }
+
+    #[test]
+    fn gap_and_justify() {
+        let font = font();
+        let block = |width| {
+            Element::<()>::still(Rc::clone(&font), Content::Stack(Vec::new()))
+                .with_fixedwidth(width)
+        };
+        let positions = |justify| {
+            let mut row = Element::still(
+                Rc::clone(&font),
+                Content::Row(vec![block(10), block(20), block(10)]),
+            )
+            .with_gap(5)
+            .with_justify(justify)
+            .with_fixedwidth(101);
+            row.layout(Constraints::default()).unwrap();
+            let xs: Vec<_> = row
+                .content
+                .children()
+                .iter()
+                .map(|c| c.position().0)
+                .collect();
+            xs
+        };
+        // Of the 101 pixels, 50 are taken up by the children and the gaps between them.
+        assert_eq!(positions(Justify::Start), [0, 15, 40]);
+        assert_eq!(positions(Justify::End), [51, 66, 91]);
+        assert_eq!(positions(Justify::Center), [25, 40, 65]);
+        assert_eq!(positions(Justify::SpaceBetween), [0, 40, 91]);
+        assert_eq!(positions(Justify::SpaceAround), [8, 40, 82]);
+
+        let mut stack =
+            Element::still(Rc::clone(&font), Content::Stack(vec![block(1), block(1)])).with_gap(7);
+        stack.layout(Constraints::default()).unwrap();
+        assert_eq!(stack.fill_size().height, 7);
+    }
+
+    #[test]
+    fn cross_align() {
+        let font = font();
+        let stack = |align| {
+            let mut stack = Element::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    text(&font, "narrow"),
+                    text(&font, "flexed").with_flex_left(true),
+                    text(&font, "limited").with_maxwidth(50),
+                ]),
+            )
+            .with_cross_align(align)
+            .with_fixedwidth(100);
+            stack.layout(Constraints::default()).unwrap();
+            stack
+        };
+        let narrow = {
+            let stack = stack(CrossAlign::Start);
+            stack.content.children()[0].overall_size().width
+        };
+
+        let centered = stack(CrossAlign::Center);
+        let children = centered.content.children();
+        assert_eq!(children[0].position().0, ((100 - narrow) / 2) as i64);
+        // A flexed side places the child, whatever the alignment of its container.
+        let flexed = children[1].overall_size().width;
+        assert_eq!(children[1].position().0, (100 - flexed) as i64);
+
+        let end = stack(CrossAlign::End);
+        assert_eq!(
+            end.content.children()[0].position().0,
+            (100 - narrow) as i64
+        );
+
+        let stretched = stack(CrossAlign::Stretch);
+        let children = stretched.content.children();
+        assert_eq!(children[0].fill_size().width, 100);
+        assert_eq!(children[1].position(), (0, 16));
+        // The size of a child still limits how far it stretches.
+        assert_eq!(children[2].fill_size().width, 50);
+    }
+
+    #[test]
+    fn scroll_bounds() {
+        let font = font();
+        let words = "one two three four five six seven eight nine ten".to_string();
+        let mut paragraph = Element::<()>::still(
+            Rc::clone(&font),
+            Content::Paragraph(WrappedText::new(words, 60, &font), Alignment::Left),
+        )
+        .with_fixedwidth(60)
+        .with_fixedheight(32);
+        paragraph.layout(Constraints::default()).unwrap();
+        let Content::Paragraph(wrapped, _) = &paragraph.content else {
+            unreachable!()
+        };
+        let lines = wrapped.lines_count();
+        assert!(lines > 2);
+        assert_eq!(paragraph.content_size().height, lines as u32 * 16);
+        assert_eq!(
+            paragraph.max_scroll(),
+            Dimensions::new(0, (lines as u32 - 2) * 16)
+        );
+        assert_eq!(paragraph.line_offset(2), Some(32));
+        assert_eq!(paragraph.line_offset(lines), None);
+        assert_eq!(text(&font, "no lines").line_offset(0), None);
+    }
+}
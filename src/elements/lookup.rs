@@ -0,0 +1,120 @@
+use super::Element;
+
+impl<D> Element<D> {
+    /// Returns the first [`Element`] in this tree with the identifier `id`.
+    ///
+    /// The tree is searched in tree order, starting with this `Element` itself. See
+    /// [`Element::with_id`].
+    pub fn get(&self, id: &str) -> Option<&Element<D>> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+        self.content
+            .children()
+            .iter()
+            .find_map(|child| child.get(id))
+    }
+
+    /// Returns the first [`Element`] in this tree with the identifier `id` mutably.
+    ///
+    /// Changes that affect the layout of the `Element` take effect at the next
+    /// [`Panel::update`](crate::Panel::update). See [`Element::get`].
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Element<D>> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+        self.content
+            .children_mut()
+            .iter_mut()
+            .find_map(|child| child.get_mut(id))
+    }
+
+    /// Returns the path to the first [`Element`] in this tree with the identifier `id`, relative
+    /// to this `Element`.
+    ///
+    /// This is the path that [`Element::descendant`] and [`Panel::focus`](crate::Panel::focus)
+    /// take. See [`Element::get`].
+    pub fn path_of(&self, id: &str) -> Option<Vec<usize>> {
+        self.path_where(|element| element.id.as_deref() == Some(id))
+    }
+
+    /// Returns the path to the first [`Element`] in this tree, in tree order, that matches the
+    /// `predicate`.
+    pub(crate) fn path_where(&self, predicate: impl Fn(&Element<D>) -> bool) -> Option<Vec<usize>> {
+        fn find<D>(
+            element: &Element<D>,
+            predicate: &impl Fn(&Element<D>) -> bool,
+            path: &mut Vec<usize>,
+        ) -> bool {
+            if predicate(element) {
+                return true;
+            }
+            for (idx, child) in element.content.children().iter().enumerate() {
+                path.push(idx);
+                if find(child, predicate, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+
+        let mut path = Vec::new();
+        find(self, &predicate, &mut path).then_some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::elements::{Alignment, Content, Element};
+    use crate::testing::font;
+
+    fn tree() -> Element<()> {
+        let font = font();
+        let text = |s: &str| {
+            Element::still(
+                Rc::clone(&font),
+                Content::Text(s.to_string(), Alignment::Left),
+            )
+        };
+        Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                text("title").with_id("title"),
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Row(vec![text("first"), text("status").with_id("status")]),
+                ),
+                text("again").with_id("status"),
+            ]),
+        )
+        .with_id("root")
+    }
+
+    #[test]
+    fn found_in_tree_order() {
+        let tree = tree();
+        assert!(std::ptr::eq(tree.get("root").unwrap(), &tree));
+        let status = tree.get("status").unwrap();
+        assert!(matches!(&status.content, Content::Text(text, _) if text == "status"));
+        assert_eq!(tree.path_of("status"), Some(vec![1, 1]));
+        assert_eq!(tree.path_of("root"), Some(vec![]));
+        assert!(tree.get("missing").is_none());
+        assert_eq!(tree.path_of("missing"), None);
+    }
+
+    #[test]
+    fn changed_through_get_mut() {
+        let mut tree = tree();
+        let title = tree.get_mut("title").unwrap();
+        title.content = Content::Text("changed".to_string(), Alignment::Right);
+        title.style.foreground = [0xff, 0x00, 0x00, 0xff];
+
+        let path = tree.path_of("title").unwrap();
+        let title = tree.descendant(&path).unwrap();
+        assert!(matches!(&title.content, Content::Text(text, _) if text == "changed"));
+        assert_eq!(title.style.foreground, [0xff, 0x00, 0x00, 0xff]);
+    }
+}
@@ -1,16 +1,59 @@
 use std::rc::Rc;
 
+use binding::Bound;
+pub use binding::{Binding, ListBinding};
+pub(crate) use damage::simplify_damage;
+pub use damage::Rect;
 use fleck::Font;
 pub use graph::Graph;
+pub use grid::{Grid, GridCell, Track};
+pub use hit::Hit;
+pub use layout::Constraints;
+pub use scrollbar::Scrollbar;
+pub use table::{Column, ColumnWidth, Table, TableBinding};
+pub use text_editor::TextEditor;
+use text_input::draw_glyphs;
+pub use text_input::{TextBinding, TextInput};
+pub use virtual_list::{RowHeight, VirtualList};
 pub use wrapped_text::WrappedText;
 
-use crate::block::DrawBlock;
-use crate::{Block, Pixel};
+use crate::block::Canvas;
+use crate::error::RenderError;
+use crate::event::Event;
+use crate::{BlendMode, Pixel};
 
+mod binding;
+mod damage;
+mod dispatch;
+mod focus;
 pub mod graph;
+mod grid;
+mod hit;
+mod layout;
+mod lookup;
+mod scrollbar;
+pub mod table;
+pub mod text_editor;
+pub mod text_input;
+mod virtual_list;
 pub mod wrapped_text;
 
 type UpdateFn<D> = fn(element: &mut Element<D>, data: &D);
+/// An update function that may capture state of its own, such as an accessor into the `data`.
+///
+/// See [`Element::with_update`].
+type BoxedUpdateFn<D> = Box<dyn FnMut(&mut Element<D>, &D)>;
+
+/// The function that updates an [`Element`], either a plain `fn` or a closure.
+enum Update<D> {
+    Fn(UpdateFn<D>),
+    Boxed(BoxedUpdateFn<D>),
+}
+/// A function that handles an [`Event`] for an [`Element`], and may mutate the `data` in response.
+///
+/// It returns whether the `Event` was handled. An `Event` that is not handled is passed on to the
+/// handler of the parent `Element`.
+type HandlerFn<D> = fn(element: &mut Element<D>, data: &mut D, event: &Event) -> bool;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub enum SizingStrategy {
@@ -37,9 +80,11 @@ pub enum SizingStrategy {
 /// the `min` and `max` dimensions.
 pub struct Size {
     pub strategy: SizingStrategy,
-    /// The unconstrained width dimension. It may fall outside of `maxwidth` and `minwidth`.
+    /// The width that was settled on when the [`Element`] was laid out. It may fall outside of
+    /// `maxwidth` and `minwidth` when these were changed since.
     baked_width: u32,
-    /// The unconstrained height dimension. It may fall outside of `maxheight` and `minheight`.
+    /// The height that was settled on when the [`Element`] was laid out. It may fall outside of
+    /// `maxheight` and `minheight` when these were changed since.
     baked_height: u32,
     pub maxwidth: Option<u32>,
     pub maxheight: Option<u32>,
@@ -61,7 +106,7 @@ impl Dimensions {
 
 type Pad = u32;
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, Hash)]
 pub struct Padding {
     pub top: Pad,
     pub bottom: Pad,
@@ -69,31 +114,154 @@ pub struct Padding {
     pub right: Pad,
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+/// How an [`Element`] takes up the room that its parent has to spare.
+///
+/// The sides are weights, where a weight of zero does not flex. The room that is left along a
+/// [`Content::Row`] or [`Content::Stack`] is shared among the flexed sides of its children in
+/// proportion to their weights, which moves the children apart. Across the row or stack, each
+/// child splits the room it leaves between its two sides in the same way.
+///
+/// Rather than moving a child, `grow` and `shrink` change its size along the row or stack. Room
+/// that is left is first shared among the children that grow, and only what they leave goes to
+/// the sides. When the children do not fit, those that shrink give up the room that is lacking,
+/// again in proportion to their weights. No pixels are lost to rounding along the way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Flex {
-    pub top: bool,
-    pub bottom: bool,
-    pub left: bool,
-    pub right: bool,
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+    pub grow: u32,
+    pub shrink: u32,
+}
+
+/// Where the children of a [`Content::Row`] or [`Content::Stack`] go along it, when there is
+/// room left that no flexed side or growing child takes up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Justify {
+    /// Packed together at the start.
+    #[default]
+    Start,
+    /// Packed together at the end.
+    End,
+    /// Packed together in the middle.
+    Center,
+    /// The first child at the start and the last child at the end, with the room shared evenly
+    /// between the children.
+    SpaceBetween,
+    /// The room shared evenly around every child, so that there is half as much room at the ends
+    /// as there is between two children.
+    SpaceAround,
+}
+
+/// Where the children of a [`Content::Row`] or [`Content::Stack`] go across it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrossAlign {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Stretched to fill the container, as far as the [`Size`] of each child allows.
+    Stretch,
 }
 
-impl Flex {
-    /// Returns the number of vertical flexes of this [`Flex`].
-    fn vertical_flexes(&self) -> usize {
-        self.top as usize + self.bottom as usize
+/// How a [`Content::Row`] or [`Content::Stack`] lines up its children.
+///
+/// A child that flexes its sides across the container is placed by its [`Flex`], rather than by
+/// the `align` of the container, unless the container stretches its children.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arrangement {
+    /// The room in pixels between every two children.
+    pub gap: u32,
+    pub justify: Justify,
+    pub align: CrossAlign,
+}
+
+/// A point on the edge or in the center of an area.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnchorPoint {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl AnchorPoint {
+    /// Returns how far along the horizontal and vertical axes this [`AnchorPoint`] lies, in
+    /// halves of the area.
+    fn halves(self) -> (u32, u32) {
+        match self {
+            AnchorPoint::TopLeft => (0, 0),
+            AnchorPoint::Top => (1, 0),
+            AnchorPoint::TopRight => (2, 0),
+            AnchorPoint::Left => (0, 1),
+            AnchorPoint::Center => (1, 1),
+            AnchorPoint::Right => (2, 1),
+            AnchorPoint::BottomLeft => (0, 2),
+            AnchorPoint::Bottom => (1, 2),
+            AnchorPoint::BottomRight => (2, 2),
+        }
     }
+}
+
+/// Where a child of a [`Content::Layers`] is placed within the fill area of its parent.
+///
+/// The `point` of the child is lined up with the same point of the parent, after which the child
+/// is moved by the offset. So an `Anchor` at [`AnchorPoint::BottomRight`] with an offset of
+/// `(-2, -2)` puts the child two pixels away from the bottom right corner.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchor {
+    pub point: AnchorPoint,
+    /// The horizontal offset in pixels, where positive values move the child to the right.
+    pub offset_x: i32,
+    /// The vertical offset in pixels, where positive values move the child down.
+    pub offset_y: i32,
+}
 
-    /// Returns the number of horizontal flexes of this [`Flex`].
-    fn horizontal_flexes(&self) -> usize {
-        self.left as usize + self.right as usize
+impl Anchor {
+    /// Creates a new [`Anchor`] at `point`, without an offset.
+    pub fn new(point: AnchorPoint) -> Self {
+        Self {
+            point,
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    pub fn with_offset(mut self, x: i32, y: i32) -> Self {
+        self.offset_x = x;
+        self.offset_y = y;
+        self
+    }
+
+    /// Returns the position of the top left corner of an area of `size` that is anchored within
+    /// `room`. When the area is larger than the `room`, the position can be negative.
+    fn position(&self, room: Dimensions, size: Dimensions) -> (i64, i64) {
+        let (hor, ver) = self.point.halves();
+        let align =
+            |room: u32, size: u32, halves: u32| (room as i64 - size as i64) * halves as i64 / 2;
+        (
+            align(room.width, size.width, hor) + self.offset_x as i64,
+            align(room.height, size.height, ver) + self.offset_y as i64,
+        )
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Style {
     pub foreground: Pixel,
+    /// The background, which may be translucent to let what lies behind the [`Element`] show
+    /// through. See [`Style::blend_mode`].
     pub background: Pixel,
     pub font: Rc<Font>,
+    /// How the pixels of the [`Element`] are combined with the pixels behind it.
+    pub blend_mode: BlendMode,
 }
 
 impl Style {
@@ -103,6 +271,7 @@ impl Style {
             foreground,
             background,
             font,
+            blend_mode: BlendMode::default(),
         }
     }
 
@@ -113,51 +282,166 @@ impl Style {
             foreground: [0x00, 0x00, 0x00, 0xff],
             background: [0xff, 0xff, 0xff, 0xff],
             font,
+            blend_mode: BlendMode::default(),
         }
     }
 }
 
+/// How a focused [`Element`] is drawn differently from an unfocused one.
+///
+/// See [`Element::with_focus_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusStyle {
+    /// Swap the `foreground` and `background` of the [`Style`].
+    Swap,
+    /// Use these colors instead of the ones of the [`Style`].
+    Colors {
+        foreground: Pixel,
+        background: Pixel,
+    },
+}
+
 pub struct Element<D> {
+    /// The identifier by which this [`Element`] can be found, see [`Element::get`].
+    pub id: Option<String>,
     pub size: Size,
     pub padding: Padding,
     pub flex: Flex,
+    /// Where this [`Element`] is placed when it is a child of a [`Content::Layers`].
+    pub anchor: Anchor,
+    /// The cells this [`Element`] covers when it is a child of a [`Content::Grid`].
+    pub cell: GridCell,
+    /// How the children are lined up when the content is a [`Content::Row`] or
+    /// [`Content::Stack`].
+    pub arrangement: Arrangement,
     pub style: Style,
-    update: Option<UpdateFn<D>>,
+    update: Option<Update<D>>,
+    handler: Option<HandlerFn<D>>,
+    binding: Option<Bound<D>>,
     pub content: Content<D>,
+    /// How far the content is scrolled down, in pixels. For a [`Content::Table`], this is the
+    /// index of the first row that is shown instead.
+    ///
+    /// See [`Element::content_size`] and [`Element::max_scroll`].
     pub scroll: Option<u32>,
+    /// How far the content is scrolled to the right, in pixels.
+    pub scroll_x: Option<u32>,
+    /// The bars that show how far the content is scrolled, if any.
+    pub scrollbar: Option<Scrollbar>,
+    /// Whether this [`Element`] can receive the keyboard focus of a [`Panel`](crate::Panel).
+    pub focusable: bool,
+    /// The style that is applied while this [`Element`] is focused, if any.
+    pub focus_style: Option<FocusStyle>,
+    focused: bool,
+    /// What this [`Element`] looked like when damage was last collected for it.
+    drawn: Option<damage::Drawn>,
+    /// Where this [`Element`] was placed within the fill area of its parent, see
+    /// [`Element::position`].
+    position: (i64, i64),
+    /// The size of the area that the content took up when it was laid out, see
+    /// [`Element::content_size`].
+    content_size: Dimensions,
 }
 
 pub enum Content<D> {
     Text(String, Alignment),
     Paragraph(WrappedText, Alignment),
     Custom { buf: Vec<Pixel>, height: u32 },
+    /// An editable line of text, optionally bound to a [`String`] in the data `D`.
+    Input(TextInput, Option<TextBinding<D>>),
+    /// Editable text that spans multiple lines, optionally bound to a [`String`] in the data `D`.
+    Editor(TextEditor, Option<TextBinding<D>>),
+    /// Rows of text in columns below a header, optionally bound to the data `D`.
+    Table(Table, Option<TableBinding<D>>),
     Row(Vec<Element<D>>),
     Stack(Vec<Element<D>>),
+    /// Children that are drawn on top of each other, in order, each placed according to its
+    /// [`Anchor`]. This is what popups, tooltips and dialogs are made of.
+    Layers(Vec<Element<D>>),
+    /// Children that are laid out in the columns and rows of a [`Grid`].
+    Grid(Grid<D>),
+    /// Rows that are built from the data `D`, of which only those in view exist.
+    VirtualList(VirtualList<D>),
+}
+
+impl<D> Content<D> {
+    /// Returns the children of a collection [`Content`], such as [`Content::Row`],
+    /// [`Content::Stack`], [`Content::Layers`] and [`Content::Grid`]. For other kinds of `Content`,
+    /// an empty slice is returned.
+    ///
+    /// The children of a [`Content::VirtualList`] are the rows in view.
+    pub fn children(&self) -> &[Element<D>] {
+        match self {
+            Content::Row(children) | Content::Stack(children) | Content::Layers(children) => {
+                children
+            }
+            Content::Grid(grid) => &grid.children,
+            Content::VirtualList(list) => &list.children,
+            Content::Text(..)
+            | Content::Paragraph(..)
+            | Content::Custom { .. }
+            | Content::Input(..)
+            | Content::Editor(..)
+            | Content::Table(..) => &[],
+        }
+    }
+
+    /// Returns the children of a collection [`Content`] mutably.
+    ///
+    /// See [`Content::children`].
+    pub fn children_mut(&mut self) -> &mut [Element<D>] {
+        match self {
+            Content::Row(children) | Content::Stack(children) | Content::Layers(children) => {
+                children
+            }
+            Content::Grid(grid) => &mut grid.children,
+            Content::VirtualList(list) => &mut list.children,
+            Content::Text(..)
+            | Content::Paragraph(..)
+            | Content::Custom { .. }
+            | Content::Input(..)
+            | Content::Editor(..)
+            | Content::Table(..) => &mut [],
+        }
+    }
 }
 
 pub mod builder {
     use super::*;
 
     pub trait ElementBuilder<D> {
-        fn with_update(self, update: UpdateFn<D>) -> Self;
+        fn with_update(self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self;
+        fn with_handler(self, handler: HandlerFn<D>) -> Self;
         fn build(self) -> Element<D>;
     }
 
     enum ContainerKind {
         Stack,
         Row,
+        Layers,
+        Grid {
+            columns: Vec<Track>,
+            rows: Vec<Track>,
+        },
     }
 
     pub struct ContainerBuilder<D> {
         font: Rc<Font>,
-        update: Option<UpdateFn<D>>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<Binding<D>>,
         kind: ContainerKind,
         children: Vec<Element<D>>,
     }
 
     impl<D> ElementBuilder<D> for ContainerBuilder<D> {
-        fn with_update(mut self, update: UpdateFn<D>) -> Self {
-            self.update = Some(update);
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
             self
         }
 
@@ -165,8 +449,17 @@ pub mod builder {
             let content = match self.kind {
                 ContainerKind::Stack => Content::Stack(self.children),
                 ContainerKind::Row => Content::Row(self.children),
+                ContainerKind::Layers => Content::Layers(self.children),
+                ContainerKind::Grid { columns, rows } => {
+                    Content::Grid(Grid::new(columns, rows, self.children))
+                }
             };
-            Element::new(self.update, content, self.font)
+            Element {
+                update: self.update,
+                handler: self.handler,
+                binding: self.binding.map(Bound::from),
+                ..Element::new(None, content, self.font)
+            }
         }
     }
 
@@ -175,6 +468,8 @@ pub mod builder {
             Self {
                 font: Rc::clone(font),
                 update: None,
+                handler: None,
+                binding: None,
                 kind: ContainerKind::Row,
                 children: Vec::new(),
             }
@@ -184,11 +479,35 @@ pub mod builder {
             Self {
                 font: Rc::clone(font),
                 update: None,
+                handler: None,
+                binding: None,
                 kind: ContainerKind::Stack,
                 children: Vec::new(),
             }
         }
 
+        fn layers(font: &Rc<Font>) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                binding: None,
+                kind: ContainerKind::Layers,
+                children: Vec::new(),
+            }
+        }
+
+        fn grid(font: &Rc<Font>, columns: Vec<Track>, rows: Vec<Track>) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                binding: None,
+                kind: ContainerKind::Grid { columns, rows },
+                children: Vec::new(),
+            }
+        }
+
         pub fn add_child(mut self, child: Element<D>) -> Self {
             self.children.push(child);
             self
@@ -198,24 +517,49 @@ pub mod builder {
             self.children.extend(children);
             self
         }
+
+        /// Bind the children to a collection in the data `D`, building one child per item.
+        ///
+        /// See [`ListBinding`].
+        pub fn with_binding(
+            mut self,
+            len: impl Fn(&D) -> usize + 'static,
+            key: impl Fn(&D, usize) -> &str + 'static,
+            template: impl Fn(&D, usize, &Rc<Font>) -> Element<D> + 'static,
+        ) -> Self {
+            self.binding = Some(Binding::List(ListBinding::new(len, key, template)));
+            self
+        }
     }
 
     pub struct TextBuilder<D> {
         font: Rc<Font>,
-        update: Option<UpdateFn<D>>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<Binding<D>>,
         alignment: Alignment,
         string: String,
     }
 
     impl<D> ElementBuilder<D> for TextBuilder<D> {
-        fn with_update(mut self, update: UpdateFn<D>) -> Self {
-            self.update = Some(update);
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
             self
         }
 
         fn build(self) -> Element<D> {
             let content = Content::Text(self.string, self.alignment);
-            Element::new(self.update, content, self.font)
+            Element {
+                update: self.update,
+                handler: self.handler,
+                binding: self.binding.map(Bound::from),
+                ..Element::new(None, content, self.font)
+            }
         }
     }
 
@@ -224,6 +568,8 @@ pub mod builder {
             Self {
                 font: Rc::clone(font),
                 update: None,
+                handler: None,
+                binding: None,
                 alignment: Default::default(),
                 string: Default::default(),
             }
@@ -242,26 +588,46 @@ pub mod builder {
             self.alignment = alignment;
             self
         }
+
+        /// Bind the string of the text to the data `D`.
+        ///
+        /// See [`Binding::Text`].
+        pub fn with_binding(mut self, get: impl Fn(&D) -> String + 'static) -> Self {
+            self.binding = Some(Binding::text(get));
+            self
+        }
     }
 
     pub struct ParagraphBuilder<D> {
         font: Rc<Font>,
-        update: Option<UpdateFn<D>>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<Binding<D>>,
         alignment: Alignment,
         width: Option<u32>,
         string: String,
     }
 
     impl<D> ElementBuilder<D> for ParagraphBuilder<D> {
-        fn with_update(mut self, update: UpdateFn<D>) -> Self {
-            self.update = Some(update);
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
             self
         }
 
         fn build(self) -> Element<D> {
             let wrapped = WrappedText::new_without_width(self.string, self.width, &self.font);
             let content = Content::Paragraph(wrapped, self.alignment);
-            Element::new(self.update, content, self.font)
+            Element {
+                update: self.update,
+                handler: self.handler,
+                binding: self.binding.map(Bound::from),
+                ..Element::new(None, content, self.font)
+            }
         }
 
     }
@@ -271,6 +637,8 @@ pub mod builder {
             Self {
                 font: Rc::clone(font),
                 update: None,
+                handler: None,
+                binding: None,
                 alignment: Default::default(),
                 width: None,
                 string: Default::default(),
@@ -292,6 +660,257 @@ pub mod builder {
             self.alignment = alignment;
             self
         }
+
+        /// Bind the text of the paragraph to the data `D`.
+        ///
+        /// See [`Binding::Paragraph`].
+        pub fn with_binding(mut self, get: impl Fn(&D) -> &str + 'static) -> Self {
+            self.binding = Some(Binding::paragraph(get));
+            self
+        }
+    }
+
+    pub struct InputBuilder<D> {
+        font: Rc<Font>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<TextBinding<D>>,
+        string: String,
+    }
+
+    impl<D> ElementBuilder<D> for InputBuilder<D> {
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
+            self
+        }
+
+        fn build(self) -> Element<D> {
+            let content = Content::Input(TextInput::new(self.string), self.binding);
+            Element {
+                update: self.update,
+                handler: self.handler,
+                focusable: true,
+                ..Element::new(None, content, self.font)
+            }
+        }
+    }
+
+    impl<D> InputBuilder<D> {
+        fn input(font: &Rc<Font>) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                binding: None,
+                string: Default::default(),
+            }
+        }
+
+        pub fn with_string(mut self, s: &str) -> Self {
+            self.string.clear();
+            self.string.push_str(s);
+            self
+        }
+
+        /// Bind the value of the text input to a [`String`] in the data `D`.
+        ///
+        /// See [`TextBinding`].
+        pub fn with_binding(
+            mut self,
+            get: fn(&D) -> &str,
+            get_mut: fn(&mut D) -> &mut String,
+        ) -> Self {
+            self.binding = Some(TextBinding { get, get_mut });
+            self
+        }
+    }
+
+    pub struct TableBuilder<D> {
+        font: Rc<Font>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<TableBinding<D>>,
+        table: Table,
+    }
+
+    impl<D> ElementBuilder<D> for TableBuilder<D> {
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
+            self
+        }
+
+        fn build(self) -> Element<D> {
+            let content = Content::Table(self.table, self.binding);
+            Element {
+                update: self.update,
+                handler: self.handler,
+                focusable: true,
+                ..Element::new(None, content, self.font)
+            }
+        }
+    }
+
+    impl<D> TableBuilder<D> {
+        fn table(font: &Rc<Font>, columns: Vec<Column>) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                binding: None,
+                table: Table::new(columns),
+            }
+        }
+
+        /// Set the room between two columns, in pixels.
+        pub fn with_spacing(mut self, spacing: u32) -> Self {
+            self.table = self.table.with_spacing(spacing);
+            self
+        }
+
+        /// Bind the rows of the table to the data `D`.
+        ///
+        /// See [`TableBinding`].
+        pub fn with_binding(
+            mut self,
+            rows: fn(&D) -> usize,
+            cell: fn(data: &D, row: usize, column: usize, cell: &mut String),
+        ) -> Self {
+            self.binding = Some(TableBinding { rows, cell });
+            self
+        }
+    }
+
+    pub struct EditorBuilder<D> {
+        font: Rc<Font>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        binding: Option<TextBinding<D>>,
+        width: Option<u32>,
+        string: String,
+    }
+
+    impl<D> ElementBuilder<D> for EditorBuilder<D> {
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
+            self
+        }
+
+        fn build(self) -> Element<D> {
+            let wrapped = WrappedText::new_without_width(self.string, self.width, &self.font);
+            let content = Content::Editor(TextEditor::new(wrapped), self.binding);
+            Element {
+                update: self.update,
+                handler: self.handler,
+                focusable: true,
+                ..Element::new(None, content, self.font)
+            }
+        }
+    }
+
+    impl<D> EditorBuilder<D> {
+        fn editor(font: &Rc<Font>) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                binding: None,
+                width: None,
+                string: Default::default(),
+            }
+        }
+
+        pub fn with_string(mut self, s: &str) -> Self {
+            self.string.clear();
+            self.string.push_str(s);
+            self
+        }
+
+        pub fn with_width(mut self, width: u32) -> Self {
+            self.width = Some(width);
+            self
+        }
+
+        /// Bind the value of the text editor to a [`String`] in the data `D`.
+        ///
+        /// See [`TextBinding`].
+        pub fn with_binding(
+            mut self,
+            get: fn(&D) -> &str,
+            get_mut: fn(&mut D) -> &mut String,
+        ) -> Self {
+            self.binding = Some(TextBinding { get, get_mut });
+            self
+        }
+    }
+
+    pub struct VirtualListBuilder<D> {
+        font: Rc<Font>,
+        update: Option<Update<D>>,
+        handler: Option<HandlerFn<D>>,
+        rows: fn(&D) -> usize,
+        row: fn(data: &D, index: usize, font: &Rc<Font>) -> Element<D>,
+        height: RowHeight,
+    }
+
+    impl<D> ElementBuilder<D> for VirtualListBuilder<D> {
+        fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+            self.update = Some(Update::Boxed(Box::new(update)));
+            self
+        }
+
+        fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+            self.handler = Some(handler);
+            self
+        }
+
+        fn build(self) -> Element<D> {
+            let content = Content::VirtualList(VirtualList::new(self.rows, self.row, self.height));
+            Element {
+                update: self.update,
+                handler: self.handler,
+                ..Element::new(None, content, self.font)
+            }
+        }
+    }
+
+    impl<D> VirtualListBuilder<D> {
+        fn virtual_list(
+            font: &Rc<Font>,
+            rows: fn(&D) -> usize,
+            row: fn(data: &D, index: usize, font: &Rc<Font>) -> Element<D>,
+        ) -> Self {
+            Self {
+                font: Rc::clone(font),
+                update: None,
+                handler: None,
+                rows,
+                row,
+                height: RowHeight::Estimated(font.height() as u32),
+            }
+        }
+
+        /// Set how tall the rows are. By default, they are estimated to be one line tall.
+        ///
+        /// See [`RowHeight`].
+        pub fn with_row_height(mut self, height: RowHeight) -> Self {
+            self.height = height;
+            self
+        }
     }
 
     impl<D> Element<D> {
@@ -303,6 +922,22 @@ pub mod builder {
             ContainerBuilder::stack(font)
         }
 
+        /// Start building a [`Content::Layers`], where the children are drawn on top of each
+        /// other. Each child is placed according to its [`Anchor`], see [`Element::with_anchor`].
+        pub fn layers_builder(font: &Rc<Font>) -> ContainerBuilder<D> {
+            ContainerBuilder::layers(font)
+        }
+
+        /// Start building a [`Content::Grid`] with these `columns` and `rows`. Each child is placed
+        /// in the cells given by its [`GridCell`], see [`Element::with_cell`].
+        pub fn grid_builder(
+            font: &Rc<Font>,
+            columns: Vec<Track>,
+            rows: Vec<Track>,
+        ) -> ContainerBuilder<D> {
+            ContainerBuilder::grid(font, columns, rows)
+        }
+
         pub fn text(s: &str, font: &Rc<Font>) -> TextBuilder<D> {
             TextBuilder::text(font).with_string(s)
         }
@@ -318,6 +953,32 @@ pub mod builder {
         pub fn empty_paragraph(font: &Rc<Font>) -> ParagraphBuilder<D> {
             ParagraphBuilder::paragraph(font)
         }
+
+        /// Start building a text input. The resulting [`Element`] is focusable.
+        pub fn text_input(s: &str, font: &Rc<Font>) -> InputBuilder<D> {
+            InputBuilder::input(font).with_string(s)
+        }
+
+        /// Start building a multi-line text editor. The resulting [`Element`] is focusable.
+        pub fn text_editor(s: &str, font: &Rc<Font>) -> EditorBuilder<D> {
+            EditorBuilder::editor(font).with_string(s)
+        }
+
+        /// Start building a table with these `columns`. The resulting [`Element`] is focusable,
+        /// so that its rows can be selected with the keyboard.
+        pub fn table(columns: Vec<Column>, font: &Rc<Font>) -> TableBuilder<D> {
+            TableBuilder::table(font, columns)
+        }
+
+        /// Start building a [`VirtualList`] that takes the number of its `rows` from the data,
+        /// and builds the rows in view through `row`.
+        pub fn virtual_list(
+            rows: fn(&D) -> usize,
+            row: fn(data: &D, index: usize, font: &Rc<Font>) -> Element<D>,
+            font: &Rc<Font>,
+        ) -> VirtualListBuilder<D> {
+            VirtualListBuilder::virtual_list(font, rows, row)
+        }
     }
 }
 
@@ -325,13 +986,27 @@ impl<D> Element<D> {
     /// Creates a new [`Element<D>`].
     pub fn new(update: Option<UpdateFn<D>>, content: Content<D>, font: Rc<Font>) -> Self {
         Self {
+            id: None,
             size: Default::default(),
             padding: Default::default(),
             flex: Default::default(),
+            anchor: Default::default(),
+            cell: Default::default(),
+            arrangement: Default::default(),
             style: Style::default_with_font(font),
-            update,
+            update: update.map(Update::Fn),
+            handler: None,
+            binding: None,
             content,
             scroll: Default::default(),
+            scroll_x: Default::default(),
+            scrollbar: Default::default(),
+            focusable: false,
+            focus_style: None,
+            focused: false,
+            drawn: None,
+            position: (0, 0),
+            content_size: Dimensions::default(),
         }
     }
 
@@ -353,6 +1028,13 @@ impl<D> Element<D> {
         Self::new(Some(update), content, font)
     }
 
+    /* id */
+    /// Set the identifier by which this [`Element`] can be found, see [`Element::get`].
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
     /* min size */
     pub fn with_minwidth(mut self, minwidth: u32) -> Self {
         self.size.minwidth = Some(minwidth);
@@ -416,23 +1098,77 @@ impl<D> Element<D> {
     }
 
     /* flex */
+    /// Set whether the top side flexes, with a weight of one. See [`Flex`].
     pub fn with_flex_top(mut self, flex: bool) -> Self {
-        self.flex.top = flex;
+        self.flex.top = flex as u32;
         self
     }
 
+    /// Set whether the bottom side flexes, with a weight of one. See [`Flex`].
     pub fn with_flex_bottom(mut self, flex: bool) -> Self {
-        self.flex.bottom = flex;
+        self.flex.bottom = flex as u32;
         self
     }
 
+    /// Set whether the left side flexes, with a weight of one. See [`Flex`].
     pub fn with_flex_left(mut self, flex: bool) -> Self {
-        self.flex.left = flex;
+        self.flex.left = flex as u32;
         self
     }
 
+    /// Set whether the right side flexes, with a weight of one. See [`Flex`].
     pub fn with_flex_right(mut self, flex: bool) -> Self {
-        self.flex.right = flex;
+        self.flex.right = flex as u32;
+        self
+    }
+
+    pub fn with_flex(mut self, flex: Flex) -> Self {
+        self.flex = flex;
+        self
+    }
+
+    /// Set the weight with which this [`Element`] grows into the room that is left in its row or
+    /// stack. See [`Flex`].
+    pub fn with_grow(mut self, weight: u32) -> Self {
+        self.flex.grow = weight;
+        self
+    }
+
+    /// Set the weight with which this [`Element`] shrinks when the children of its row or stack
+    /// do not fit. See [`Flex`].
+    pub fn with_shrink(mut self, weight: u32) -> Self {
+        self.flex.shrink = weight;
+        self
+    }
+
+    /* arrangement */
+    /// Set the room in pixels between the children of a [`Content::Row`] or [`Content::Stack`].
+    pub fn with_gap(mut self, gap: u32) -> Self {
+        self.arrangement.gap = gap;
+        self
+    }
+
+    pub fn with_justify(mut self, justify: Justify) -> Self {
+        self.arrangement.justify = justify;
+        self
+    }
+
+    pub fn with_cross_align(mut self, align: CrossAlign) -> Self {
+        self.arrangement.align = align;
+        self
+    }
+
+    /* anchor */
+    /// Set where this [`Element`] is placed when it is a child of a [`Content::Layers`].
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /* cell */
+    /// Set the cells this [`Element`] covers when it is a child of a [`Content::Grid`].
+    pub fn with_cell(mut self, cell: GridCell) -> Self {
+        self.cell = cell;
         self
     }
 
@@ -452,123 +1188,96 @@ impl<D> Element<D> {
         self
     }
 
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.style.blend_mode = blend_mode;
+        self
+    }
+
     pub fn with_scroll(mut self, scroll: u32) -> Self {
         self.scroll = Some(scroll);
         self
     }
-}
 
-impl<D> Element<D> {
-    /// Configure the internal size information based on the [`Element`]'s content and constraints.
+    pub fn with_scroll_x(mut self, scroll: u32) -> Self {
+        self.scroll_x = Some(scroll);
+        self
+    }
+
+    /// Set the [`Scrollbar`] that shows how far this [`Element`] is scrolled.
+    pub fn with_scrollbar(mut self, scrollbar: Scrollbar) -> Self {
+        self.scrollbar = Some(scrollbar);
+        self
+    }
+
+    /* update */
+    /// Set the function that updates this [`Element`] from the `data` when
+    /// [`Panel::update`](crate::Panel::update) is called, replacing any previous one.
     ///
-    /// For container [`Content`]s, this method is called recursively.
+    /// Unlike the `fn` that [`Element::dynamic`] takes, this may be a closure that captures state,
+    /// so one updater can be reused with different accessors into the `data`.
+    pub fn with_update(mut self, update: impl FnMut(&mut Element<D>, &D) + 'static) -> Self {
+        self.update = Some(Update::Boxed(Box::new(update)));
+        self
+    }
+
+    /// Bind the content of this [`Element`] to the data `D`, replacing any previous [`Binding`].
     ///
-    /// After calling this method, the `Element`'s width and height as accessed through
-    /// [`Element::fill_size`] or [`Element::overall_size`] are reliably defined and are valid
-    /// until the content of it or any of its children are mutated.
+    /// The value is written after the update function, if there is one, has run.
+    pub fn with_binding(mut self, binding: Binding<D>) -> Self {
+        self.binding = Some(binding.into());
+        self
+    }
+
+    /* events */
+    /// Set the handler that is called when an [`Event`] is dispatched to this [`Element`].
     ///
-    /// In case of an `Element` with content [`Content::Paragraph`], the inner text is wrapped to
-    /// the `maxwidth`, and the `width` and `height` are subsequently calculated based on these
-    /// wrapped lines.
-    pub(crate) fn bake_size(&mut self, maxwidth_hint: Option<u32>) {
-        {
-            let Size {
-                minwidth,
-                maxwidth,
-                minheight,
-                maxheight,
-                ..
-            } = self.size();
-            if maxwidth.is_some() && minwidth.is_some() {
-                assert!(
-                    maxwidth >= minwidth,
-                    "minwidth ({minwidth:?}) cannot be greater than maxwidth ({maxwidth:?})"
-                );
-            }
-            if maxheight.is_some() && minheight.is_some() {
-                assert!(
-                    maxheight >= minheight,
-                    "minheight ({minheight:?}) cannot be greater than maxheight ({maxheight:?})"
-                );
-            }
-        }
-        let width;
-        let height;
-        match &mut self.content {
-            Content::Text(text, _) => {
-                width = self.style.font.determine_width(text) as u32;
-                height = self.style.font.height() as u32;
-            }
-            Content::Paragraph(wrapped, _) => {
-                wrapped.rewrap(self.size.maxwidth.or(maxwidth_hint), &self.style.font);
-                width = wrapped
-                    .lines()
-                    .map(|line| self.style.font.determine_width(line) as u32)
-                    .max()
-                    .unwrap_or_default();
-                height = (self.style.font.height() * wrapped.lines_count()) as u32;
-            }
-            Content::Custom { buf, height: h } => {
-                width = buf.len() as u32 / *h;
-                height = *h;
-            }
-            Content::Row(children) | Content::Stack(children) => {
-                // TODO: See whether this collect alloc can be eliminated. Perhaps unzip?
-                let sizes: Vec<_> = children
-                    .iter_mut()
-                    .map(|child| {
-                        child.bake_size(self.size.maxwidth);
-                        child.overall_size()
-                    })
-                    .collect();
-                let widths = sizes.iter().map(|size| size.width);
-                let heights = sizes.iter().map(|size| size.height);
-                match self.content {
-                    Content::Row(_) => {
-                        width = widths.sum();
-                        height = heights.max().unwrap_or_default();
-                    }
-                    Content::Stack(_) => {
-                        height = heights.sum();
-                        width = widths.max().unwrap_or_default();
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
+    /// See [`Panel::handle_event`](crate::Panel::handle_event).
+    pub fn with_handler(mut self, handler: HandlerFn<D>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
 
-        match self.size.strategy {
-            SizingStrategy::Whatever => {
-                self.size.baked_width = width;
-                self.size.baked_height = height;
-            }
-            SizingStrategy::Chonker => {
-                self.size.baked_width = self.size.maxwidth.unwrap_or_default().max(width);
-                self.size.baked_height = self.size.maxheight.unwrap_or_default().max(height);
-            }
-            SizingStrategy::Smollest => {
-                // Ah okay wow just realized what the problem is while I was doing something else.
-                // Wait no I was wrong.
-                self.size.baked_width = self.size.minwidth.unwrap_or(width).min(width);
-                self.size.baked_height = self.size.minheight.unwrap_or(height).min(height);
-            }
-        }
+    /* focus */
+    /// Set whether this [`Element`] can receive the keyboard focus.
+    ///
+    /// See [`Panel::focus`](crate::Panel::focus).
+    pub fn with_focusable(mut self, focusable: bool) -> Self {
+        self.focusable = focusable;
+        self
+    }
+
+    pub fn with_focus_style(mut self, focus_style: FocusStyle) -> Self {
+        self.focus_style = Some(focus_style);
+        self
     }
+}
 
+impl<D> Element<D> {
     pub(crate) fn update(&mut self, data: &D) {
-        if let Some(update) = self.update {
-            update(self, data)
+        // The update function is taken out for a moment, so that it can borrow the `Element`.
+        if let Some(mut update) = self.update.take() {
+            match &mut update {
+                Update::Fn(update) => update(self, data),
+                Update::Boxed(update) => update(self, data),
+            }
+            self.update = Some(update);
         }
-
-        // TODO: (easy) Break out the 'is it a collection type' logic to a
-        // `Content::is_collection(&self) -> bool`.
+        self.read_binding(data);
+        self.read_rows(data);
         match &mut self.content {
-            // Update the chirren.
-            Content::Row(elements) | Content::Stack(elements) => {
-                elements.iter_mut().for_each(|element| element.update(data))
+            Content::Input(input, Some(binding)) => input.set_text((binding.get)(data)),
+            Content::Editor(editor, Some(binding)) => {
+                editor.set_text((binding.get)(data), &self.style.font)
             }
+            Content::Table(table, Some(binding)) => table.read(binding, data),
             _ => {}
-        };
+        }
+
+        // Update the chirren.
+        self.content
+            .children_mut()
+            .iter_mut()
+            .for_each(|element| element.update(data));
     }
 }
 
@@ -583,24 +1292,26 @@ impl<D> Element<D> {
     /// These [`Dimensions`] _exclude_ the padding and only report the inner size of the `Element`.
     pub fn fill_size(&self) -> Dimensions {
         if let Content::Custom { buf, height } = &self.content {
-            let width = buf.len() as u32 / height;
+            let width = (buf.len() as u32).checked_div(*height).unwrap_or_default();
             return Dimensions {
                 width,
                 height: *height,
             };
         }
+        // Note that we cannot use `clamp` here, since a misbehaving update function may have set
+        // a minimum that is greater than the maximum. In that case, the maximum wins.
         let size = self.size();
         let width = match (size.minwidth, size.maxwidth) {
             (None, None) => size.baked_width,
             (None, Some(maxwidth)) => size.baked_width.min(maxwidth),
             (Some(minwidth), None) => size.baked_width.max(minwidth),
-            (Some(minwidth), Some(maxwidth)) => size.baked_width.clamp(minwidth, maxwidth),
+            (Some(minwidth), Some(maxwidth)) => size.baked_width.max(minwidth).min(maxwidth),
         };
         let height = match (size.minheight, size.maxheight) {
             (None, None) => size.baked_height,
             (None, Some(maxheight)) => size.baked_height.min(maxheight),
             (Some(minheight), None) => size.baked_height.max(minheight),
-            (Some(minheight), Some(maxheight)) => size.baked_height.clamp(minheight, maxheight),
+            (Some(minheight), Some(maxheight)) => size.baked_height.max(minheight).min(maxheight),
         };
         Dimensions { width, height }
     }
@@ -656,146 +1367,130 @@ impl<D> Element<D> {
         Self::include_padding(self.fill_size(), self.padding)
     }
 
-    /// Returns a tuple with the horizontal then vertical room per flex for `children`.
+    /// Returns the horizontal and vertical distance in pixels by which the content of this
+    /// [`Element`] is scrolled.
     ///
-    /// # Panics
-    ///
-    /// Can only be called on an element with a [`Content::Row`] or [`Content::Stack`]. If it is
-    /// called on an [`Element`] with a different content kind, the function will panic.
-    fn room_per_flex(&self, children: &[Element<D>]) -> (u32, u32) {
-        let children_sizes = children.iter().map(|child| child.overall_size());
-        let (children_width, children_height) = match self.content {
-            Content::Row(_) => (
-                children_sizes.clone().map(|size| size.width).sum(),
-                children_sizes
-                    .map(|size| size.height)
-                    .max()
-                    .unwrap_or_default(),
-            ),
-            Content::Stack(_) => (
-                children_sizes
-                    .clone()
-                    .map(|size| size.width)
-                    .max()
-                    .unwrap_or_default(),
-                children_sizes.map(|size| size.height).sum(),
-            ),
-            _ => unimplemented!(
-                "this function can only produce meaningful results for Row and Stack Content"
-            ),
+    /// A [`Content::Table`] scrolls through its rows by itself, so it only scrolls horizontally.
+    fn scroll_offset(&self) -> (u32, u32) {
+        let scroll_y = match self.content {
+            Content::Table(..) => 0,
+            _ => self.scroll.unwrap_or_default(),
         };
-        let flex_room_hor = self.overall_size().width.saturating_sub(children_width);
-        let flex_room_ver = self.overall_size().height.saturating_sub(children_height);
-        let flexes = children.iter().map(|child| child.flex);
-        let flexes_hor: u32 = flexes
-            .clone()
-            .map(|flex| flex.horizontal_flexes() as u32)
-            .sum();
-        let flexes_ver: u32 = flexes.map(|flex| flex.vertical_flexes() as u32).sum();
-        (
-            flex_room_hor.checked_div(flexes_hor).unwrap_or_default(),
-            flex_room_ver.checked_div(flexes_ver).unwrap_or_default(),
-        )
+        (self.scroll_x.unwrap_or_default(), scroll_y)
+    }
+
+    /// Returns the offset of the top left corner of the child at `index`, relative to the top left
+    /// corner of this [`Element`].
+    ///
+    /// Both corners include the padding of their `Element`. The offset takes the scroll of this
+    /// `Element` into account, which means it can be negative.
+    ///
+    /// Returns [`None`] if there is no child at `index`.
+    fn child_offset(&self, index: usize) -> Option<(i64, i64)> {
+        let (x, y) = self.content.children().get(index)?.position;
+        let (scroll_x, scroll_y) = self.scroll_offset();
+        Some((
+            self.padding.left as i64 + x - scroll_x as i64,
+            self.padding.top as i64 + y - scroll_y as i64,
+        ))
     }
 }
 
-impl<D> DrawBlock for Element<D> {
-    fn block(&self) -> Block {
+impl<D> Element<D> {
+    /// Paint this [`Element`] onto a [`Canvas`] that spans its overall size.
+    ///
+    /// Only the pixels that the `canvas` is clipped to are touched, and children that lie outside
+    /// of them are not drawn at all. This makes it cheap to redraw a small part of a large tree.
+    /// Nothing is allocated along the way.
+    pub(crate) fn paint(&self, canvas: &mut Canvas) -> Result<(), RenderError> {
+        if canvas.is_empty() {
+            return Ok(());
+        }
+        let (foreground, background) = self.colors();
+        canvas.set_blend_mode(self.style.blend_mode);
+        canvas.fill(background);
+
         let Dimensions { width, height } = self.fill_size();
-        let mut inner_block = Block::new(width, height, self.style.background);
+        let (left, top) = (self.padding.left as i64, self.padding.top as i64);
+        let mut fill = canvas.view(left, top, width, height);
+        if fill.is_empty() {
+            return Ok(());
+        }
+
+        // The content is drawn in full, scrolled within the fill area that it is clipped to.
+        let (scroll_x, scroll_y) = self.scroll_offset();
+        let content = self.content_size;
+        if scroll_y > content.height {
+            return Err(RenderError::ScrollOutOfRange {
+                path: Vec::new(),
+                scroll: scroll_y,
+                content_height: content.height,
+            });
+        }
+        if scroll_x > content.width {
+            return Err(RenderError::HorizontalScrollOutOfRange {
+                path: Vec::new(),
+                scroll: scroll_x,
+                content_width: content.width,
+            });
+        }
+        let mut canvas = fill.view(
+            -(scroll_x as i64),
+            -(scroll_y as i64),
+            width.max(content.width),
+            height.max(content.height),
+        );
+        let font = &self.style.font;
         match &self.content {
-            Content::Text(text, alignment) => draw_text(
-                &mut inner_block,
-                text,
-                *alignment,
-                &self.style.font,
-                self.style.foreground,
-                self.style.background,
-            ),
+            Content::Text(text, alignment) => {
+                draw_text(&mut canvas, text, *alignment, font, foreground, background)
+            }
             Content::Paragraph(wrapped, alignment) => {
-                let mut y = 0;
-                for line in wrapped.lines() {
-                    let mut line_block = Block::new(
-                        width,
-                        self.style.font.height() as u32,
-                        self.style.background,
-                    );
-                    draw_text(
-                        &mut line_block,
-                        line,
-                        *alignment,
-                        &self.style.font,
-                        self.style.foreground,
-                        self.style.background,
-                    );
-                    inner_block.paint(&line_block, 0, y);
-                    y += line_block.height;
-
-                    if let Some(maxheight) = self.size.maxheight {
-                        if y > maxheight {
-                            break;
-                        }
-                    }              
+                let line_height = font.height() as u32;
+                let rows = canvas.visible_rows();
+                let first = (rows.start / line_height as i64) as usize;
+                for (idx, range) in wrapped.line_ranges_from(first).enumerate() {
+                    let y = ((first + idx) as u32 * line_height) as i64;
+                    if y >= rows.end {
+                        break;
+                    }
+                    let line = &wrapped.text()[range];
+                    let mut canvas = canvas.view(0, y, canvas.width(), line_height);
+                    draw_text(&mut canvas, line, *alignment, font, foreground, background);
                 }
             }
-            Content::Custom { buf, height: h } => {
-                assert_eq!(buf.len() as u32 / h, width); // FIXME: Is this redundant?
-                assert_eq!(*h, height);
-                inner_block.buf.copy_from_slice(buf);
+            Content::Input(input, _) => {
+                input.draw(&mut canvas, font, foreground, background, self.focused)
             }
-            Content::Row(children) => {
-                let (room_per_flex_hor, room_per_flex_ver) = self.room_per_flex(children);
-                let mut x = 0;
-                for child in children {
-                    if child.flex.left {
-                        x += room_per_flex_hor
-                    }
-                    inner_block.paint(&child.block(), x, child.flex.top as u32 * room_per_flex_ver);
-                    if child.flex.right {
-                        x += room_per_flex_hor
-                    }
-                    x += child.overall_size().width;
-                }
+            Content::Editor(editor, _) => {
+                editor.draw(&mut canvas, font, foreground, background, self.focused)
             }
-            Content::Stack(children) => {
-                let (room_per_flex_hor, room_per_flex_ver) = self.room_per_flex(children);
-
-                let children_height: u32 = children.iter().map(|child| child.overall_size().height).sum::<u32>();
-                let mut block = Block::new(self.overall_size().width, children_height, self.style.background);
-
-                let mut y = 0;
-                for child in children {
-                    if child.flex.top {
-                        y += room_per_flex_ver
-                    }
-
-                    block.paint(
-                        &child.block(),
-                        child.flex.left as u32 * room_per_flex_hor,
-                        y,
-                    );
-                    if child.flex.bottom {
-                        y += room_per_flex_ver
-                    }
-                    y += child.overall_size().height;
+            // The buffer is validated when laying out, but an update may have changed it since.
+            Content::Custom { buf, .. } => canvas.copy_from(buf, width),
+            // The rows of a table come and go with the data, so the scroll is kept within them.
+            Content::Table(table, _) => {
+                let first = (self.scroll.unwrap_or_default() as usize).min(table.rows());
+                table.draw(&mut canvas, font, foreground, background, first)
+            }
+            Content::Row(children)
+            | Content::Stack(children)
+            | Content::Layers(children)
+            | Content::Grid(Grid { children, .. })
+            | Content::VirtualList(VirtualList { children, .. }) => {
+                for (idx, child) in children.iter().enumerate() {
+                    let (x, y) = child.position;
+                    let Dimensions { width, height } = child.overall_size();
+                    let mut canvas = canvas.view(x, y, width, height);
+                    child.paint(&mut canvas).map_err(|err| err.within(idx))?;
                 }
-                
-                let scroll_index: usize = self.scroll.unwrap_or(0) as usize;
-                let start_index: usize = scroll_index * inner_block.width as usize;
-
-                // FIXME: with a max height of zero start_index will always be invalid
-                inner_block.buf = block.buf[start_index..].to_vec();
             }
         }
-
-        let Dimensions { width, height } = self.overall_size();
-        let mut padded_block = Block::new(width, height, self.style.background);
-        padded_block.paint(&inner_block, self.padding.left, self.padding.top);
-        padded_block
+        self.draw_scrollbars(&mut fill, foreground, background);
+        Ok(())
     }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Alignment {
     #[default]
     Left,
@@ -803,91 +1498,26 @@ pub enum Alignment {
     Right,
 }
 
-#[inline(always)]
+/// Draw a single line of `text` onto the `canvas`, aligned within its width.
 fn draw_text(
-    block: &mut Block,
+    canvas: &mut Canvas,
     text: &str,
     alignment: Alignment,
     font: &Font,
     foreground: Pixel,
     background: Pixel,
 ) {
-    // TODO: This implementation is not ideal. Since a Block is created to fit the entire
-    // line of text to then be cut off in a manner that depends on the alignment, we do
-    // more allocations than is necessary, and we may render more characters into that
-    // Block than is necessary (since they are eventually thrown away due to alignment).
-    // A better way of implementing this would be to do the proper computer maths with
-    // indices, so that it can all be done through iterators (which evaluate lazily).
-    // As a note for such an implementation in the future, see the state at or around
-    // commit e945006.
-
-    let scrap_width = font.determine_width(text);
-    // TODO: Perhaps this case can be handled a little more gracefully, but that will
-    // require a more holistic view of the whole layout "engine" in a later stage.
-    // Postponing ;)
-    if block.width == 0 || scrap_width == 0 {
-        return; // Nothing to even draw, here. Why expend the energy?
-    }
-    let mut scrap = Block::new(scrap_width as u32, font.height() as u32, background);
-    let glyphs = text.chars().flat_map(|ch| font.glyph(ch));
-    let mut x0 = 0;
-    for glyph in glyphs {
-        let glyph_width = glyph.width as usize;
-        for (y, row) in glyph.enumerate() {
-            for (xg, cell) in row.enumerate() {
-                let x = x0 + xg;
-                // TODO: This may be more efficient than what I did in Graph. May be
-                // worth investigating which is better.
-                scrap.buf[y * scrap.width as usize + x] =
-                    if cell { foreground } else { background };
-            }
-        }
-        x0 += glyph_width;
-    }
-
-    match alignment {
-        Alignment::Left => {
-            let end = block.width.min(scrap.width) as usize;
-            block
-                .rows_mut()
-                .zip(scrap.rows())
-                .for_each(|(row, scrap_row)| {
-                    row[..end].copy_from_slice(&scrap_row[..end]);
-                })
-        }
-        // TODO: The first two branches do the exact same thing. Figure out how to do this
-        // nicely.
+    let text_width = font.determine_width(text) as i64;
+    let width = canvas.width() as i64;
+    let x0 = match alignment {
+        Alignment::Left => 0,
         // TODO: Decide on the actually correct behavior for Alignment::Center in this case.
-        Alignment::Center if scrap.width >= block.width => {
-            let end = block.width.min(scrap.width) as usize;
-            block
-                .rows_mut()
-                .zip(scrap.rows())
-                .for_each(|(row, scrap_row)| {
-                    row[..end].copy_from_slice(&scrap_row[..end]);
-                })
-        }
-        Alignment::Center => {
-            let remainder = block.width - scrap.width;
-            let start = remainder as usize / 2;
-            block
-                .rows_mut()
-                .zip(scrap.rows())
-                .for_each(|(row, scrap_row)| {
-                    row[start..start + scrap.width as usize].copy_from_slice(scrap_row);
-                })
-        }
-        Alignment::Right => {
-            let rstart = block.width.saturating_sub(scrap.width) as usize;
-            let sstart = scrap.width.saturating_sub(block.width) as usize;
-            block
-                .rows_mut()
-                .zip(scrap.rows())
-                .for_each(|(row, scrap_row)| {
-                    row[rstart..].copy_from_slice(&scrap_row[sstart..]);
-                })
-        }
-    }
+        Alignment::Center if text_width >= width => 0,
+        Alignment::Center => (width - text_width) / 2,
+        Alignment::Right => width - text_width,
+    };
+    let colors = (foreground, background);
+    draw_glyphs(canvas, font, text, 0..text.len(), (x0, 0), None, colors);
 }
 
 #[cfg(test)]
@@ -895,6 +1525,8 @@ mod tests {
     use fleck::Font;
 
     use super::*;
+    use crate::error::LayoutError;
+    use crate::testing::font;
 
     type Data = ();
 
@@ -909,7 +1541,7 @@ mod tests {
     #[test]
     fn fill_size() {
         let mut elem = create_element();
-        elem.bake_size(None);
+        elem.layout(Constraints::default()).unwrap();
 
         assert_eq!(elem.fill_size(), Dimensions::new(69, 16));
     }
@@ -918,7 +1550,7 @@ mod tests {
     fn min_fill_size() {
         macro_rules! bake_and_compare {
             ($elem:ident, $dim:expr) => {
-                $elem.bake_size(None);
+                $elem.layout(Constraints::default()).unwrap();
                 assert_eq!($elem.min_fill_size(), $dim);
             };
         }
@@ -943,7 +1575,7 @@ mod tests {
     fn max_fill_size() {
         macro_rules! bake_and_compare {
             ($elem:ident, $dim:expr) => {
-                $elem.bake_size(None);
+                $elem.layout(Constraints::default()).unwrap();
                 assert_eq!($elem.max_fill_size(), $dim);
             };
         }
@@ -967,7 +1599,7 @@ mod tests {
     #[test]
     fn zero_padding() {
         let mut elem = create_element();
-        elem.bake_size(None);
+        elem.layout(Constraints::default()).unwrap();
         assert_eq!(elem.overall_size(), Dimensions::new(69, 16));
 
         elem = elem
@@ -975,7 +1607,7 @@ mod tests {
             .with_padding_bottom(0)
             .with_padding_left(0)
             .with_padding_right(0);
-        elem.bake_size(None);
+        elem.layout(Constraints::default()).unwrap();
         assert_eq!(elem.overall_size(), Dimensions::new(69, 16));
         assert_eq!(elem.overall_size(), elem.fill_size(),);
     }
@@ -983,7 +1615,7 @@ mod tests {
     #[test]
     fn with_padding() {
         let mut elem = create_element();
-        elem.bake_size(None);
+        elem.layout(Constraints::default()).unwrap();
         assert_eq!(elem.overall_size(), Dimensions::new(69, 16));
 
         elem = elem
@@ -991,7 +1623,623 @@ mod tests {
             .with_padding_bottom(34)
             .with_padding_left(56)
             .with_padding_right(78);
-        elem.bake_size(None);
+        elem.layout(Constraints::default()).unwrap();
         assert_eq!(elem.overall_size(), Dimensions::new(203, 62));
     }
+
+    #[test]
+    fn invalid_constraints() {
+        let font = font();
+        let mut stack = Element::<Data>::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                create_element(),
+                Element::still(Rc::clone(&font), Content::Row(vec![create_element()]))
+                    .with_minheight(20)
+                    .with_maxheight(10),
+            ]),
+        );
+        assert_eq!(
+            stack.layout(Constraints::default()),
+            Err(LayoutError::InvalidHeight {
+                path: vec![1],
+                minheight: 20,
+                maxheight: 10
+            })
+        );
+
+        let Content::Stack(children) = &mut stack.content else {
+            unreachable!()
+        };
+        children[1].size.maxheight = None;
+        children[1].content = Content::Row(vec![Element::still(
+            Rc::clone(&font),
+            Content::Custom {
+                buf: vec![[0; 4]; 7],
+                height: 2,
+            },
+        )]);
+        assert_eq!(
+            stack.layout(Constraints::default()),
+            Err(LayoutError::InvalidCustom {
+                path: vec![1, 0],
+                len: 7,
+                height: 2
+            })
+        );
+    }
+
+    #[test]
+    fn scroll_out_of_range() {
+        let font = font();
+        let mut stack = Element::<Data>::still(
+            Rc::clone(&font),
+            Content::Stack(vec![create_element(), create_element()]),
+        )
+        .with_scroll(32);
+        stack.layout(Constraints::default()).unwrap();
+        assert!(crate::testing::render(&stack).is_ok());
+
+        stack.scroll = Some(33);
+        assert_eq!(
+            crate::testing::render(&stack).err(),
+            Some(RenderError::ScrollOutOfRange {
+                path: Vec::new(),
+                scroll: 33,
+                content_height: 32
+            })
+        );
+
+        stack.scroll = None;
+        stack.scroll_x = Some(70);
+        assert_eq!(
+            crate::testing::render(&stack).err(),
+            Some(RenderError::HorizontalScrollOutOfRange {
+                path: Vec::new(),
+                scroll: 70,
+                content_width: 69
+            })
+        );
+    }
+
+    #[test]
+    fn scroll_is_clamped_after_relayout() {
+        fn resize(element: &mut Element<u32>, data: &u32) {
+            element.size.maxwidth = Some(*data);
+            element.size.minwidth = Some(*data);
+        }
+
+        let font = font();
+        let words = "the quick brown fox jumps over the lazy dog ".repeat(20);
+        let paragraph = Content::Paragraph(WrappedText::new(words, 100, &font), Alignment::Left);
+        let elements = Element::dynamic(resize, font, paragraph).with_maxheight(48);
+        let mut panel = crate::Panel::new(elements, [0; 4], [0xff; 4], 100).unwrap();
+        panel.update().unwrap();
+        panel.elements.scroll = Some(panel.elements.max_scroll().height);
+        assert!(panel.render().is_ok());
+
+        // Widening the paragraph leaves fewer lines to scroll through than it was scrolled by.
+        *panel.data_mut() = 400;
+        panel.update().unwrap();
+        let scroll = panel.elements.scroll.unwrap();
+        assert!(scroll > 0);
+        assert_eq!(scroll, panel.elements.max_scroll().height);
+        assert!(panel.render().is_ok());
+    }
+
+    #[test]
+    fn failing_update() {
+        fn shrink(element: &mut Element<u32>, data: &u32) {
+            element.size.maxwidth = Some(*data);
+        }
+
+        let font = font();
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Row(vec![Element::dynamic(
+                shrink,
+                font,
+                Content::Text("Hello, world.".to_string(), Alignment::default()),
+            )
+            .with_minwidth(40)]),
+        );
+        let mut panel = crate::Panel::new(elements, [0; 4], [0xff; 4], 100).unwrap();
+        assert!(panel.update().is_ok());
+
+        *panel.data_mut() = 10;
+        assert!(matches!(
+            panel.update(),
+            Err(LayoutError::InvalidWidth { ref path, .. }) if path == &[0]
+        ));
+        // Even though the layout is broken, the panel can still be drawn.
+        assert!(panel.render().is_ok());
+    }
+
+    #[test]
+    fn closure_update() {
+        // One updater, reused with a different accessor for each element.
+        fn show(lens: fn(&(u32, u32)) -> u32) -> impl FnMut(&mut Element<(u32, u32)>, &(u32, u32)) {
+            let mut updates = 0;
+            move |element, data| {
+                updates += 1;
+                let Content::Text(text, _) = &mut element.content else {
+                    unreachable!()
+                };
+                *text = format!("{} after {updates}", lens(data));
+            }
+        }
+
+        let font = font();
+        let text = || Content::Text(String::new(), Alignment::default());
+        let elements = Element::still(
+            Rc::clone(&font),
+            Content::Stack(vec![
+                Element::still(Rc::clone(&font), text()).with_update(show(|data| data.0)),
+                Element::still(Rc::clone(&font), text()).with_update(show(|data| data.1)),
+            ]),
+        );
+        let mut panel = crate::Panel::new(elements, [0; 4], [0xff; 4], (3, 4)).unwrap();
+        panel.update().unwrap();
+        *panel.data_mut() = (5, 6);
+        panel.update().unwrap();
+
+        let texts: Vec<_> = panel
+            .elements
+            .content
+            .children()
+            .iter()
+            .map(|child| match &child.content {
+                Content::Text(text, _) => text.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(texts, ["5 after 2", "6 after 2"]);
+    }
+
+    mod snapshots {
+        use super::*;
+        use crate::testing::{assert_snapshot, font};
+
+        #[test]
+        fn text_alignment() {
+            let font = font();
+            let line = |s: &str, alignment, background| {
+                Element::still(Rc::clone(&font), Content::Text(s.to_string(), alignment))
+                    .with_strategy(SizingStrategy::Chonker)
+                    .with_maxwidth(120)
+                    .with_background(background)
+            };
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    line("left", Alignment::Left, [0xff, 0xaa, 0xaa, 0xff]),
+                    line("center", Alignment::Center, [0xaa, 0xff, 0xaa, 0xff]),
+                    line("right", Alignment::Right, [0xaa, 0xaa, 0xff, 0xff]),
+                ]),
+            );
+            assert_snapshot("text_alignment", 120, stack);
+        }
+
+        #[test]
+        fn blend_modes() {
+            let font = font();
+            let line = |s: &str, background, blend_mode| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(s.to_string(), Alignment::Left),
+                )
+                .with_strategy(SizingStrategy::Chonker)
+                .with_maxwidth(100)
+                .with_foreground([0x00, 0x00, 0x00, 0x80])
+                .with_background(background)
+                .with_blend_mode(blend_mode)
+            };
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    line("over", [0x00, 0x00, 0xff, 0x80], BlendMode::SourceOver),
+                    line("multiply", [0xaa, 0xaa, 0xff, 0xff], BlendMode::Multiply),
+                    line("replace", [0x00, 0x00, 0xff, 0x40], BlendMode::Replace),
+                ]),
+            )
+            .with_padding_left(8)
+            .with_padding_right(8)
+            .with_background([0xff, 0xcc, 0x00, 0xff]);
+            assert_snapshot("blend_modes", 116, stack);
+        }
+
+        #[test]
+        fn layers() {
+            let font = font();
+            let text = |s: &str, background| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(s.to_string(), Alignment::Left),
+                )
+                .with_padding_left(2)
+                .with_padding_right(2)
+                .with_background(background)
+            };
+            let base = Element::still(
+                Rc::clone(&font),
+                Content::Paragraph(
+                    WrappedText::new(
+                        "the layers are drawn on top of this text, in order".to_string(),
+                        120,
+                        &font,
+                    ),
+                    Alignment::Left,
+                ),
+            )
+            .with_maxwidth(120)
+            .with_background([0xff, 0xcc, 0x00, 0xff]);
+            let layers = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Layers(vec![
+                    base,
+                    text("dialog", [0x00, 0x00, 0xff, 0x80])
+                        .with_anchor(Anchor::new(AnchorPoint::Center)),
+                    text("tip", [0xaa, 0xff, 0xaa, 0xff])
+                        .with_anchor(Anchor::new(AnchorPoint::BottomRight).with_offset(-2, -2)),
+                    // Sticks out of the top left corner, and is cut off there.
+                    text("corner", [0xff, 0xaa, 0xaa, 0xff])
+                        .with_anchor(Anchor::new(AnchorPoint::TopLeft).with_offset(-8, -4)),
+                ]),
+            );
+            assert_snapshot("layers", 120, layers);
+        }
+
+        #[test]
+        fn grid() {
+            let font = font();
+            let text = |s: &str, column, row, background| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(s.to_string(), Alignment::Left),
+                )
+                .with_cell(GridCell::new(column, row))
+                .with_background(background)
+            };
+            let label = [0xaa, 0xaa, 0xff, 0xff];
+            let value = [0xaa, 0xff, 0xaa, 0xff];
+            let grid = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Grid(Grid::new(
+                    vec![Track::Fit, Track::Fixed(8), Track::Flex(1)],
+                    vec![Track::Fit, Track::Fixed(4)],
+                    vec![
+                        // A header that spans all columns and is centered within them.
+                        text("telemetry", 0, 0, [0xff, 0xcc, 0x00, 0xff])
+                            .with_cell(GridCell::new(0, 0).with_span(3, 1))
+                            .with_flex_left(true)
+                            .with_flex_right(true),
+                        text("speed", 0, 2, label),
+                        text("12", 2, 2, value).with_flex_left(true),
+                        text("temperature", 0, 3, label),
+                        text("21.5", 2, 3, value).with_flex_left(true),
+                        text("load", 0, 4, label).with_padding_left(8),
+                        text("99%", 2, 4, value),
+                    ],
+                )),
+            )
+            .with_strategy(SizingStrategy::Chonker)
+            .with_maxwidth(160)
+            .with_padding_left(4)
+            .with_padding_right(4);
+            assert_snapshot("grid", 168, grid);
+        }
+
+        #[test]
+        fn table() {
+            let font = font();
+            let mut table = Table::new(vec![
+                Column::new("name").with_width(ColumnWidth::Max(60)),
+                Column::new("size").with_alignment(Alignment::Right),
+            ])
+            .with_spacing(6);
+            for (name, size) in [
+                ("notes.txt", "3"),
+                ("a very long name.png", "14012"),
+                ("src", "-"),
+                ("target", "-"),
+                ("cream12.uf2", "2880"),
+                ("Cargo.toml", "412"),
+            ] {
+                table.push_row([name, size]);
+            }
+            table.select(Some(3));
+            let line_height = font.height() as u32;
+            // The header stays put while the rows below it scroll by one.
+            let table = Element::<Data>::still(Rc::clone(&font), Content::Table(table, None))
+                .with_maxheight(line_height * 5 + 1)
+                .with_scroll(1)
+                .with_padding_left(2)
+                .with_padding_right(2)
+                .with_background([0xff, 0xcc, 0x00, 0xff]);
+            assert_snapshot("table", 100, table);
+        }
+
+        #[test]
+        fn row_flex() {
+            let font = font();
+            let row = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Row(vec![
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Text("left".to_string(), Alignment::Left),
+                    )
+                    .with_padding_left(4)
+                    .with_background([0xff, 0xaa, 0xaa, 0xff])
+                    .with_flex_right(true),
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Text("middle".to_string(), Alignment::Left),
+                    )
+                    .with_background([0xaa, 0xff, 0xaa, 0xff])
+                    .with_flex_top(true)
+                    .with_flex_right(true),
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Text("right".to_string(), Alignment::Left),
+                    )
+                    .with_minheight(32)
+                    .with_background([0xaa, 0xaa, 0xff, 0xff]),
+                ]),
+            )
+            .with_strategy(SizingStrategy::Chonker)
+            .with_maxwidth(200);
+            assert_snapshot("row_flex", 200, row);
+        }
+
+        #[test]
+        fn split_panes() {
+            let font = font();
+            let pane = |label: &str, background: Pixel, grow: u32| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Stack(vec![Element::still(
+                        Rc::clone(&font),
+                        Content::Text(label.to_string(), Alignment::Left),
+                    )
+                    .with_flex_left(true)
+                    .with_flex_right(true)]),
+                )
+                .with_background(background)
+                .with_grow(grow)
+            };
+            let row = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Row(vec![
+                    pane("two", [0xff, 0xaa, 0xaa, 0xff], 2),
+                    pane("one", [0xaa, 0xaa, 0xff, 0xff], 1),
+                ]),
+            )
+            .with_fixedwidth(120)
+            .with_fixedheight(32);
+            assert_snapshot("split_panes", 120, row);
+        }
+
+        #[test]
+        fn arrangement() {
+            let font = font();
+            let label = |text: &str, background: Pixel| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(text.to_string(), Alignment::Left),
+                )
+                .with_background(background)
+            };
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    label("centered", [0xff, 0xaa, 0xaa, 0xff]),
+                    label("also", [0xaa, 0xff, 0xaa, 0xff]),
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Row(vec![
+                            label("a", [0xaa, 0xaa, 0xff, 0xff]),
+                            label("b", [0xaa, 0xaa, 0xff, 0xff]),
+                            label("c", [0xaa, 0xaa, 0xff, 0xff]),
+                        ]),
+                    )
+                    .with_justify(Justify::SpaceBetween)
+                    .with_fixedwidth(120),
+                ]),
+            )
+            .with_gap(4)
+            .with_cross_align(CrossAlign::Center)
+            .with_fixedwidth(120);
+            assert_snapshot("arrangement", 120, stack);
+        }
+
+        #[test]
+        fn scrolled() {
+            let font = font();
+            let text =
+                "Any element scrolls through its content, which is clipped to its fill area.";
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Paragraph(
+                            WrappedText::new(text.to_string(), 100, &font),
+                            Alignment::Left,
+                        ),
+                    )
+                    .with_fixedheight(40)
+                    .with_scroll(20)
+                    .with_padding_left(4)
+                    .with_padding_top(4)
+                    .with_background([0xff, 0xee, 0xaa, 0xff]),
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Row(vec![
+                            Element::still(
+                                Rc::clone(&font),
+                                Content::Text("sideways".to_string(), Alignment::Left),
+                            )
+                            .with_background([0xaa, 0xff, 0xaa, 0xff]),
+                            Element::still(
+                                Rc::clone(&font),
+                                Content::Text("scrolling".to_string(), Alignment::Left),
+                            )
+                            .with_background([0xaa, 0xaa, 0xff, 0xff]),
+                        ]),
+                    )
+                    .with_fixedwidth(60)
+                    .with_scroll_x(24),
+                ]),
+            )
+            .with_gap(4);
+            assert_snapshot("scrolled", 108, stack);
+        }
+
+        #[test]
+        fn scrollbar() {
+            let font = font();
+            let text = "The thumb of a scrollbar spans the part of the content that is in view, \
+                and the track spans all of it.";
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Paragraph(
+                            WrappedText::new(text.to_string(), 100, &font),
+                            Alignment::Left,
+                        ),
+                    )
+                    .with_fixedheight(40)
+                    .with_scroll(24)
+                    .with_padding_left(4)
+                    .with_padding_top(4)
+                    .with_scrollbar(Scrollbar::new(3))
+                    .with_background([0xff, 0xee, 0xaa, 0xff]),
+                    Element::still(
+                        Rc::clone(&font),
+                        Content::Row(vec![
+                            Element::still(
+                                Rc::clone(&font),
+                                Content::Text("sideways".to_string(), Alignment::Left),
+                            ),
+                            Element::still(
+                                Rc::clone(&font),
+                                Content::Text("scrolling".to_string(), Alignment::Left),
+                            ),
+                        ]),
+                    )
+                    .with_fixedwidth(60)
+                    .with_scroll_x(24)
+                    .with_scrollbar(Scrollbar::new(2).with_always_visible(true))
+                    .with_background([0xaa, 0xaa, 0xff, 0xff]),
+                ]),
+            )
+            .with_gap(4);
+            assert_snapshot("scrollbar", 108, stack);
+        }
+
+        #[test]
+        fn paragraph() {
+            let font = font();
+            let text = "Or does the error always come first? It was, after all, Grace Hopper \
+                who, writing the software for the first electronic programmable computer, \
+                introduced the terms \"bug\" and \"debug\" to computer programming.";
+            let paragraph = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Paragraph(
+                    WrappedText::new(text.to_string(), 160, &font),
+                    Alignment::Center,
+                ),
+            )
+            .with_padding_top(8)
+            .with_padding_left(8)
+            .with_padding_right(8)
+            .with_maxwidth(160);
+            assert_snapshot("paragraph", 176, paragraph);
+        }
+
+        #[test]
+        fn stack_scroll() {
+            let font = font();
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(
+                    (0..8)
+                        .map(|i| {
+                            Element::still(
+                                Rc::clone(&font),
+                                Content::Text(format!("line {i}"), Alignment::Left),
+                            )
+                        })
+                        .collect(),
+                ),
+            )
+            .with_fixedheight(48)
+            .with_scroll(24);
+            assert_snapshot("stack_scroll", 100, stack);
+        }
+
+        #[test]
+        fn custom() {
+            let font = font();
+            let graph = Graph::from(
+                (0..32)
+                    .map(|x| (x % 8) as f32)
+                    .collect::<std::collections::VecDeque<_>>(),
+            );
+            let mut buf = vec![[0; 4]; 32 * 8];
+            let style = Style::new(
+                [0x00, 0x00, 0xff, 0xff],
+                [0xff, 0xff, 0xaa, 0xff],
+                Rc::clone(&font),
+            );
+            graph.paint(&mut buf, 8, &style);
+            let custom = Element::<Data>::still(font, Content::Custom { buf, height: 8 })
+                .with_padding_left(2)
+                .with_padding_bottom(2);
+            assert_snapshot("custom", 34, custom);
+        }
+
+        #[test]
+        fn text_input() {
+            let font = font();
+            let input = |text: &str| {
+                let mut input = TextInput::new(text.to_string());
+                input.move_left(true);
+                input.move_left(true);
+                Element::<Data>::still(Rc::clone(&font), Content::Input(input, None))
+                    .with_fixedwidth(80)
+                    .with_padding_left(2)
+                    .with_padding_top(2)
+                    .with_padding_bottom(2)
+            };
+            let mut focused = input("a line that is too long to fit");
+            focused.set_focused(true);
+            let stack = Element::<Data>::still(
+                Rc::clone(&font),
+                Content::Stack(vec![focused, input("not focused")]),
+            );
+            assert_snapshot("text_input", 82, stack);
+        }
+
+        #[test]
+        fn text_editor() {
+            let font = font();
+            let text = "Or does the error always come first? It was, after all, Grace Hopper.";
+            let mut editor = TextEditor::new(WrappedText::new(text.to_string(), 100, &font));
+            editor.set_cursor(20);
+            editor.move_down(true, &font);
+            editor.move_right(true);
+            let content = Content::Editor(editor, None);
+            let mut element = Element::<Data>::still(Rc::clone(&font), content)
+                .with_maxwidth(101)
+                .with_padding_left(2)
+                .with_padding_top(2)
+                .with_padding_bottom(2);
+            element.set_focused(true);
+            assert_snapshot("text_editor", 103, element);
+        }
+    }
 }
@@ -0,0 +1,405 @@
+use super::layout::Axis;
+use super::{Content, Dimensions, Element, Rect};
+use crate::block::Canvas;
+use crate::event::{Button, Event};
+use crate::Pixel;
+
+/// Bars along the right and bottom edges of an [`Element`] that show which part of its content is
+/// in view, see [`Element::with_scrollbar`].
+///
+/// A bar is shown for each direction in which the content does not fit into the fill area. It is
+/// drawn over the content, at the edge of the fill area. The track of a bar takes the background
+/// of the `Element`, and the thumb takes its foreground. The thumb spans the part of the content
+/// that is in view, but it is never shorter than the bar is wide.
+///
+/// The thumb can be dragged with the pointer. Pressing the track beside the thumb moves the middle
+/// of the thumb to the pointer, after which it can be dragged as well. Dragging sets the
+/// [`Element::scroll`] or [`Element::scroll_x`] of the `Element`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scrollbar {
+    /// The width of a bar in pixels.
+    pub width: u32,
+    /// Whether the bars are shown even when all of the content is in view.
+    pub always_visible: bool,
+    /// The direction of the bar whose thumb is being dragged, and the distance from the start of
+    /// the thumb to the pointer.
+    grab: Option<(Axis, u32)>,
+}
+
+impl Scrollbar {
+    /// Creates a new [`Scrollbar`] with bars that are `width` pixels wide.
+    pub fn new(width: u32) -> Self {
+        Self {
+            width,
+            always_visible: false,
+            grab: None,
+        }
+    }
+
+    pub fn with_always_visible(mut self, always_visible: bool) -> Self {
+        self.always_visible = always_visible;
+        self
+    }
+
+    /// Returns whether the thumb of this [`Scrollbar`] is being dragged.
+    pub fn is_dragged(&self) -> bool {
+        self.grab.is_some()
+    }
+}
+
+impl Default for Scrollbar {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+/// How far an [`Element`] scrolls in one direction, in the unit it scrolls by.
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    scroll: u32,
+    content: u32,
+    view: u32,
+}
+
+impl Extent {
+    fn max_scroll(self) -> u32 {
+        self.content.saturating_sub(self.view)
+    }
+
+    /// Returns the start and length of the thumb within a track of `length`, where the thumb is
+    /// at least `min` long.
+    fn thumb(self, length: u32, min: u32) -> (u32, u32) {
+        let max_scroll = self.max_scroll();
+        if max_scroll == 0 {
+            return (0, length);
+        }
+        let size = (length as u64 * self.view as u64 / self.content as u64) as u32;
+        let size = size.max(min).min(length);
+        let scroll = self.scroll.min(max_scroll) as u64;
+        let start = (length - size) as u64 * scroll / max_scroll as u64;
+        (start as u32, size)
+    }
+
+    /// Returns the scroll that puts the start of a thumb of `size` at `start` within a track of
+    /// `length`, rounded to the nearest unit.
+    fn scroll_at(self, start: i64, length: u32, size: u32) -> u32 {
+        let room = length.saturating_sub(size) as u64;
+        if room == 0 {
+            return 0;
+        }
+        let start = start.clamp(0, room as i64) as u64;
+        ((start * self.max_scroll() as u64 + room / 2) / room) as u32
+    }
+}
+
+impl<D> Element<D> {
+    /// Returns how far this [`Element`] scrolls along `axis`.
+    ///
+    /// A [`Content::Table`] scrolls through its rows vertically, so that extent is in rows.
+    fn extent(&self, axis: Axis) -> Extent {
+        let fill = self.fill_size();
+        match (axis, &self.content) {
+            (Axis::Vertical, Content::Table(table, _)) => Extent {
+                scroll: (self.scroll.unwrap_or_default() as usize).min(table.rows()) as u32,
+                content: table.rows() as u32,
+                view: table.page_rows(fill.height, &self.style.font) as u32,
+            },
+            (Axis::Vertical, _) => Extent {
+                scroll: self.scroll.unwrap_or_default(),
+                content: self.content_size.height,
+                view: fill.height,
+            },
+            (Axis::Horizontal, _) => Extent {
+                scroll: self.scroll_x.unwrap_or_default(),
+                content: self.content_size.width,
+                view: fill.width,
+            },
+        }
+    }
+
+    /// Returns the track of the bar along `axis` within the fill area, if it is shown.
+    ///
+    /// Where both bars are shown, the vertical bar leaves the corner to the horizontal one.
+    fn scrollbar_track(&self, axis: Axis) -> Option<Rect> {
+        let scrollbar = self.scrollbar?;
+        let shown = |axis| scrollbar.always_visible || self.extent(axis).max_scroll() > 0;
+        if !shown(axis) {
+            return None;
+        }
+        let Dimensions { width, height } = self.fill_size();
+        let bar = scrollbar.width;
+        let track = match axis {
+            Axis::Vertical => {
+                let corner = if shown(Axis::Horizontal) { bar } else { 0 };
+                Rect::new(
+                    width.saturating_sub(bar),
+                    0,
+                    bar.min(width),
+                    height.saturating_sub(corner),
+                )
+            }
+            Axis::Horizontal => Rect::new(0, height.saturating_sub(bar), width, bar.min(height)),
+        };
+        (!track.is_empty()).then_some(track)
+    }
+
+    /// Returns the start and length of the thumb along `axis` within its `track`.
+    fn thumb(&self, axis: Axis, track: Rect) -> (u32, u32) {
+        let (length, width) = axis.split(Dimensions::new(track.width, track.height));
+        self.extent(axis).thumb(length, width)
+    }
+
+    /// Scroll along `axis` such that the thumb starts at `start` within its `track`.
+    fn scroll_thumb_to(&mut self, axis: Axis, track: Rect, start: i64) {
+        let (length, width) = axis.split(Dimensions::new(track.width, track.height));
+        let extent = self.extent(axis);
+        let (_, size) = extent.thumb(length, width);
+        let scroll = Some(extent.scroll_at(start, length, size));
+        match axis {
+            Axis::Vertical => self.scroll = scroll,
+            Axis::Horizontal => self.scroll_x = scroll,
+        }
+    }
+
+    /// Returns whether the position `x`, `y` lies on one of the bars of this [`Element`].
+    ///
+    /// The position is relative to the top left corner of the fill area.
+    pub(super) fn on_scrollbar(&self, x: u32, y: u32) -> bool {
+        self.scrollbar_at(x, y).is_some()
+    }
+
+    /// Returns the bar that the position `x`, `y` within the fill area lies on, if any.
+    fn scrollbar_at(&self, x: u32, y: u32) -> Option<(Axis, Rect)> {
+        [Axis::Vertical, Axis::Horizontal]
+            .into_iter()
+            .filter_map(|axis| Some((axis, self.scrollbar_track(axis)?)))
+            .find(|(_, track)| track.contains(&Rect::new(x, y, 1, 1)))
+    }
+
+    /// Draw the bars of the [`Scrollbar`] of this [`Element`], if it has one, onto a `canvas`
+    /// that spans its fill area.
+    pub(super) fn draw_scrollbars(
+        &self,
+        canvas: &mut Canvas,
+        foreground: Pixel,
+        background: Pixel,
+    ) {
+        for axis in [Axis::Vertical, Axis::Horizontal] {
+            let Some(track) = self.scrollbar_track(axis) else {
+                continue;
+            };
+            let (x, y) = (track.x as i64, track.y as i64);
+            let mut canvas = canvas.view(x, y, track.width, track.height);
+            canvas.fill(background);
+            let (start, size) = self.thumb(axis, track);
+            let mut thumb = match axis {
+                Axis::Vertical => canvas.view(0, start as i64, track.width, size),
+                Axis::Horizontal => canvas.view(start as i64, 0, size, track.height),
+            };
+            thumb.fill(foreground);
+        }
+    }
+
+    /// Apply a pointer [`Event`] to the [`Scrollbar`] of this [`Element`], if it has one.
+    ///
+    /// Returns whether the scrollbar responded to the `Event`.
+    pub(super) fn drag_scrollbar(&mut self, event: Event) -> bool {
+        let Some(scrollbar) = self.scrollbar else {
+            return false;
+        };
+        // The bars are placed within the fill area.
+        let event = event.translated(self.padding.left as i64, self.padding.top as i64);
+        let along = |axis: Axis, track: Rect, x: u32, y: u32| {
+            let (along, _) = axis.split(Dimensions::new(x, y));
+            let (start, _) = axis.split(Dimensions::new(track.x, track.y));
+            along as i64 - start as i64
+        };
+        match (event, scrollbar.grab) {
+            (
+                Event::PointerPress {
+                    x,
+                    y,
+                    button: Button::Left,
+                },
+                _,
+            ) => {
+                let Some((axis, track)) = self.scrollbar_at(x, y) else {
+                    return false;
+                };
+                let (start, size) = self.thumb(axis, track);
+                let along = along(axis, track, x, y);
+                let offset = along - start as i64;
+                let grab = if (0..size as i64).contains(&offset) {
+                    offset as u32
+                } else {
+                    size / 2
+                };
+                self.scroll_thumb_to(axis, track, along - grab as i64);
+                self.set_grab(Some((axis, grab)));
+                true
+            }
+            (Event::PointerMove { x, y }, Some((axis, grab))) => {
+                if let Some(track) = self.scrollbar_track(axis) {
+                    let along = along(axis, track, x, y);
+                    self.scroll_thumb_to(axis, track, along - grab as i64);
+                }
+                true
+            }
+            (Event::PointerRelease { .. }, Some(_)) => {
+                self.set_grab(None);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn set_grab(&mut self, grab: Option<(Axis, u32)>) {
+        if let Some(scrollbar) = &mut self.scrollbar {
+            scrollbar.grab = grab;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::elements::{Alignment, Constraints};
+    use crate::testing::font;
+    use crate::Panel;
+
+    fn stack(lines: usize) -> Element<()> {
+        let font = font();
+        let children = (0..lines)
+            .map(|i| {
+                Element::still(
+                    Rc::clone(&font),
+                    Content::Text(format!("line {i}"), Alignment::Left),
+                )
+            })
+            .collect();
+        let mut stack = Element::still(font, Content::Stack(children))
+            .with_fixedwidth(60)
+            .with_fixedheight(40)
+            .with_padding_top(5)
+            .with_scrollbar(Scrollbar::new(4));
+        stack.layout(Constraints::default()).unwrap();
+        stack
+    }
+
+    fn press(x: u32, y: u32) -> Event {
+        Event::PointerPress {
+            x,
+            y,
+            button: Button::Left,
+        }
+    }
+
+    #[test]
+    fn thumb_follows_the_scroll() {
+        let extent = |scroll| Extent {
+            scroll,
+            content: 400,
+            view: 100,
+        };
+        assert_eq!(extent(0).thumb(100, 4), (0, 25));
+        assert_eq!(extent(150).thumb(100, 4), (37, 25));
+        assert_eq!(extent(300).thumb(100, 4), (75, 25));
+        // A scroll beyond the content keeps the thumb at the end.
+        assert_eq!(extent(900).thumb(100, 4), (75, 25));
+        // The thumb is never shorter than the minimum.
+        let long = Extent {
+            scroll: 0,
+            content: 10_000,
+            view: 100,
+        };
+        assert_eq!(long.thumb(100, 4), (0, 4));
+        // When everything is in view, the thumb spans the track.
+        let short = Extent {
+            scroll: 0,
+            content: 50,
+            view: 100,
+        };
+        assert_eq!(short.thumb(100, 4), (0, 100));
+
+        // Placing the thumb gives back the scroll, within the track.
+        assert_eq!(extent(0).scroll_at(37, 100, 25), 148);
+        assert_eq!(extent(0).scroll_at(-10, 100, 25), 0);
+        assert_eq!(extent(0).scroll_at(90, 100, 25), 300);
+        assert_eq!(short.scroll_at(10, 100, 100), 0);
+    }
+
+    #[test]
+    fn bars_are_shown_when_needed() {
+        let mut element = stack(2);
+        assert_eq!(element.scrollbar_track(Axis::Vertical), None);
+        element.scrollbar = Some(Scrollbar::new(4).with_always_visible(true));
+        assert_eq!(
+            element.scrollbar_track(Axis::Vertical),
+            Some(Rect::new(56, 0, 4, 36))
+        );
+        assert_eq!(
+            element.scrollbar_track(Axis::Horizontal),
+            Some(Rect::new(0, 36, 60, 4))
+        );
+
+        let element = stack(8);
+        assert_eq!(
+            element.scrollbar_track(Axis::Vertical),
+            Some(Rect::new(56, 0, 4, 40))
+        );
+        assert_eq!(element.scrollbar_track(Axis::Horizontal), None);
+        assert!(element.on_scrollbar(57, 39));
+        assert!(!element.on_scrollbar(55, 39));
+    }
+
+    #[test]
+    fn drag_the_thumb() {
+        let mut element = stack(8);
+        let max_scroll = element.max_scroll().height;
+        assert_eq!(max_scroll, 8 * 16 - 40);
+        // The thumb is 40 * 40 / 128 = 12 pixels long. Grab it below the padding.
+        assert!(element.drag_scrollbar(press(57, 5 + 8)));
+        assert!(element.scrollbar.is_some_and(|bar| bar.is_dragged()));
+        assert_eq!(element.scroll, Some(0));
+        assert!(element.drag_scrollbar(Event::PointerMove { x: 30, y: 5 + 20 }));
+        assert_eq!(element.scroll, Some(38));
+        // Dragging beyond the track stops at the end.
+        assert!(element.drag_scrollbar(Event::PointerMove { x: 30, y: 200 }));
+        assert_eq!(element.scroll, Some(max_scroll));
+        let release = Event::PointerRelease {
+            x: 30,
+            y: 200,
+            button: Button::Left,
+        };
+        assert!(element.drag_scrollbar(release));
+        assert!(!element.drag_scrollbar(Event::PointerMove { x: 30, y: 5 }));
+        assert_eq!(element.scroll, Some(max_scroll));
+
+        // Pressing the track moves the middle of the thumb to the pointer.
+        assert!(element.drag_scrollbar(press(57, 5 + 20)));
+        assert_eq!(element.scroll, Some(44));
+        // A press beside the bars is left alone.
+        assert!(!element.drag_scrollbar(press(20, 5 + 18)));
+    }
+
+    #[test]
+    fn drag_through_the_panel() {
+        let mut panel = Panel::new(stack(8), [0x00; 4], [0xff; 4], ()).unwrap();
+        assert!(panel.handle_event(press(57, 5 + 8)));
+        // The pointer has left the panel, but the thumb still follows it.
+        assert!(panel.handle_event(Event::PointerMove { x: 100, y: 100 }));
+        assert_eq!(
+            panel.elements.scroll,
+            panel.elements.max_scroll().height.into()
+        );
+        let release = Event::PointerRelease {
+            x: 100,
+            y: 100,
+            button: Button::Left,
+        };
+        assert!(panel.handle_event(release));
+        assert!(!panel.handle_event(Event::PointerMove { x: 57, y: 13 }));
+    }
+}
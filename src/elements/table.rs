@@ -0,0 +1,488 @@
+use fleck::Font;
+
+use super::{draw_text, Alignment, Dimensions};
+use crate::block::Canvas;
+use crate::event::{Event, Key};
+use crate::Pixel;
+
+/// The height of the line that separates the header from the rows of a [`Table`].
+const HEADER_RULE: u32 = 1;
+
+/// How the width of a [`Column`] is determined.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnWidth {
+    /// As wide as the widest cell in the column, including the header.
+    #[default]
+    Fit,
+    /// Exactly this many pixels wide.
+    Fixed(u32),
+    /// As wide as the widest cell in the column, but no wider than this many pixels.
+    Max(u32),
+}
+
+/// A column of a [`Table`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Column {
+    pub header: String,
+    pub width: ColumnWidth,
+    pub alignment: Alignment,
+}
+
+impl Column {
+    /// Creates a new [`Column`] with a `header`, that fits its cells and aligns them to the left.
+    pub fn new(header: &str) -> Self {
+        Self {
+            header: header.to_string(),
+            width: ColumnWidth::default(),
+            alignment: Alignment::default(),
+        }
+    }
+
+    pub fn with_width(mut self, width: ColumnWidth) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+}
+
+/// A pair of accessors that bind the rows of a [`Table`] to the data `D` of a
+/// [`Panel`](crate::Panel).
+///
+/// When the [`Panel`](crate::Panel) is updated, the `Table` takes on the number of rows returned
+/// by `rows`, and every cell is filled in by `cell`.
+pub struct TableBinding<D> {
+    pub rows: fn(&D) -> usize,
+    /// Write the text of the cell at `row` and `column` into the `String`, which starts out empty.
+    pub cell: fn(data: &D, row: usize, column: usize, cell: &mut String),
+}
+
+impl<D> Clone for TableBinding<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for TableBinding<D> {}
+
+/// Rows of text in [`Column`]s, below a header that stays put while the rows scroll.
+///
+/// Cells that do not fit into their column are cut short with an ellipsis. A single row can be
+/// selected, which is highlighted by swapping the foreground and background colors.
+///
+/// The `scroll` of the [`Element`](super::Element) that holds a `Table` is the index of the first
+/// row that is shown, rather than a number of pixels.
+#[derive(Debug, Clone, Hash)]
+pub struct Table {
+    columns: Vec<Column>,
+    /// The text of the cells, row by row.
+    cells: Vec<String>,
+    rows: usize,
+    selected: Option<usize>,
+    /// The room between two columns, in pixels.
+    spacing: u32,
+    /// The width of each column, which is set when the table is laid out.
+    widths: Vec<u32>,
+}
+
+impl Table {
+    /// Creates a new [`Table`] with these `columns`, and without any rows.
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            cells: Vec::new(),
+            rows: 0,
+            selected: None,
+            spacing: 0,
+            widths: Vec::new(),
+        }
+    }
+
+    /// Set the room between two columns, in pixels.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Returns the number of rows, not counting the header.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the text of the cell at `row` and `column`, if there is such a cell.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&str> {
+        if row >= self.rows || column >= self.columns.len() {
+            return None;
+        }
+        Some(&self.cells[row * self.columns.len() + column])
+    }
+
+    /// Add a row with the text of its cells. Missing cells are left empty, and cells beyond the
+    /// last column are ignored.
+    pub fn push_row<S: AsRef<str>>(&mut self, cells: impl IntoIterator<Item = S>) {
+        let count = self.columns.len();
+        let mut cells = cells.into_iter();
+        self.cells.extend((0..count).map(|_| {
+            cells
+                .next()
+                .map(|cell| cell.as_ref().to_string())
+                .unwrap_or_default()
+        }));
+        self.rows += 1;
+    }
+
+    /// Remove all rows.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.rows = 0;
+        self.selected = None;
+    }
+
+    /// Returns the index of the selected row, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Select the row at `row`, or none at all. A row that does not exist cannot be selected.
+    pub fn select(&mut self, row: Option<usize>) {
+        self.selected = row.filter(|&row| row < self.rows);
+    }
+
+    /// Take on the rows of the `data` through the `binding`.
+    ///
+    /// The `String`s of the cells are reused, so that this does not allocate when the rows did not
+    /// grow. If the selected row no longer exists, the selection is cleared.
+    pub(crate) fn read<D>(&mut self, binding: &TableBinding<D>, data: &D) {
+        let rows = (binding.rows)(data);
+        let count = self.columns.len();
+        self.cells.resize_with(rows * count, String::new);
+        for (idx, cell) in self.cells.iter_mut().enumerate() {
+            cell.clear();
+            (binding.cell)(data, idx / count, idx % count, cell);
+        }
+        self.rows = rows;
+        self.select(self.selected);
+    }
+
+    /// Work out the width of every column, and return the size of the whole table with all of
+    /// its rows.
+    pub(super) fn measure(&mut self, font: &Font) -> Dimensions {
+        let count = self.columns.len();
+        self.widths.clear();
+        for (idx, column) in self.columns.iter().enumerate() {
+            let cells = self.cells.iter().skip(idx).step_by(count.max(1));
+            let fit = || {
+                cells
+                    .map(|cell| font.determine_width(cell) as u32)
+                    .fold(font.determine_width(&column.header) as u32, u32::max)
+            };
+            self.widths.push(match column.width {
+                ColumnWidth::Fit => fit(),
+                ColumnWidth::Fixed(width) => width,
+                ColumnWidth::Max(width) => fit().min(width),
+            });
+        }
+        let spacing = self.spacing * count.saturating_sub(1) as u32;
+        let line_height = font.height() as u32;
+        Dimensions {
+            width: self.widths.iter().sum::<u32>() + spacing,
+            height: header_height(font) + self.rows as u32 * line_height,
+        }
+    }
+
+    /// Returns the number of rows that fit into a table of `height`, below the header.
+    pub(super) fn page_rows(&self, height: u32, font: &Font) -> usize {
+        (height.saturating_sub(header_height(font)) / font.height() as u32) as usize
+    }
+
+    /// Returns the row to scroll to, starting from the `first` row that is shown, such that the
+    /// selected row is within the `page` of rows that fit into the table.
+    pub(super) fn scroll_to_selection(&self, first: usize, page: usize) -> usize {
+        match self.selected {
+            Some(row) if row < first => row,
+            Some(row) if row >= first + page => (row + 1).saturating_sub(page),
+            _ => first,
+        }
+    }
+
+    /// Move the selection according to an [`Event`], where the `first` row is at the top of a
+    /// `page` of rows that fit into the table.
+    ///
+    /// Returns whether the `Event` concerned the table.
+    pub(super) fn handle_event(
+        &mut self,
+        event: &Event,
+        font: &Font,
+        first: usize,
+        page: usize,
+    ) -> bool {
+        let last = self.rows.checked_sub(1);
+        let current = self.selected;
+        let row = match *event {
+            Event::KeyPress { key, .. } => {
+                let row = match key {
+                    Key::Up => current.map_or(last, |row| Some(row.saturating_sub(1))),
+                    Key::Down => current.map_or(Some(0), |row| Some(row + 1)),
+                    Key::PageUp => current.map(|row| row.saturating_sub(page.max(1))),
+                    Key::PageDown => current.map_or(Some(0), |row| Some(row + page.max(1))),
+                    Key::Home => Some(0),
+                    Key::End => last,
+                    Key::Escape => None,
+                    _ => return false,
+                };
+                // Moving past either end stops at the end.
+                row.zip(last).map(|(row, last)| row.min(last))
+            }
+            // A press below the last row clears the selection.
+            Event::PointerPress { y, .. } => {
+                let Some(y) = y.checked_sub(header_height(font)) else {
+                    return false;
+                };
+                Some(first + (y / font.height() as u32) as usize)
+            }
+            _ => return false,
+        };
+        self.select(row);
+        true
+    }
+
+    /// Draw the header and the rows from the `first` one onwards.
+    pub(super) fn draw(
+        &self,
+        canvas: &mut Canvas,
+        font: &Font,
+        foreground: Pixel,
+        background: Pixel,
+        first: usize,
+    ) {
+        let line_height = font.height() as u32;
+        let width = canvas.width();
+        let headers = self.columns.iter().map(|column| column.header.as_str());
+        self.draw_row(canvas, headers, font, foreground, background);
+        canvas
+            .view(0, line_height as i64, width, HEADER_RULE)
+            .fill(foreground);
+
+        let rows = canvas.visible_rows();
+        let count = self.columns.len();
+        for row in first..self.rows {
+            let y = (header_height(font) + (row - first) as u32 * line_height) as i64;
+            if y >= rows.end {
+                break;
+            }
+            let cells = self.cells[row * count..][..count]
+                .iter()
+                .map(String::as_str);
+            let (foreground, background) = match self.selected == Some(row) {
+                true => {
+                    canvas.view(0, y, width, line_height).fill(foreground);
+                    (background, foreground)
+                }
+                false => (foreground, background),
+            };
+            let mut canvas = canvas.view(0, y, width, line_height);
+            self.draw_row(&mut canvas, cells, font, foreground, background);
+        }
+    }
+
+    /// Draw the `cells` of one row at the top of the `canvas`.
+    fn draw_row<'a>(
+        &self,
+        canvas: &mut Canvas,
+        cells: impl Iterator<Item = &'a str>,
+        font: &Font,
+        foreground: Pixel,
+        background: Pixel,
+    ) {
+        let line_height = font.height() as u32;
+        let mut x = 0;
+        for ((cell, column), &width) in cells.zip(&self.columns).zip(&self.widths) {
+            let mut canvas = canvas.view(x, 0, width, line_height);
+            draw_cell(
+                &mut canvas,
+                cell,
+                column.alignment,
+                font,
+                (foreground, background),
+            );
+            x += (width + self.spacing) as i64;
+        }
+    }
+}
+
+/// Returns the height of the header of a [`Table`], including the rule below it.
+fn header_height(font: &Font) -> u32 {
+    font.height() as u32 + HEADER_RULE
+}
+
+/// Draw the text of a cell, and cut it short with an ellipsis if it does not fit.
+fn draw_cell(
+    canvas: &mut Canvas,
+    text: &str,
+    alignment: Alignment,
+    font: &Font,
+    (foreground, background): (Pixel, Pixel),
+) {
+    let width = canvas.width();
+    if font.determine_width(text) as u32 <= width {
+        draw_text(canvas, text, alignment, font, foreground, background);
+        return;
+    }
+
+    let ellipsis = match font.glyph('…') {
+        Some(_) => "…",
+        None => "...",
+    };
+    let room = width.saturating_sub(font.determine_width(ellipsis) as u32);
+    let mut end = 0;
+    let mut prefix_width = 0;
+    for (idx, ch) in text.char_indices() {
+        let glyph_width = font.glyph(ch).map_or(0, |glyph| glyph.width as u32);
+        if prefix_width + glyph_width > room {
+            break;
+        }
+        prefix_width += glyph_width;
+        end = idx + ch.len_utf8();
+    }
+    let line_height = canvas.height();
+    draw_text(
+        canvas,
+        &text[..end],
+        Alignment::Left,
+        font,
+        foreground,
+        background,
+    );
+    let mut canvas = canvas.view(prefix_width as i64, 0, width - prefix_width, line_height);
+    draw_text(
+        &mut canvas,
+        ellipsis,
+        Alignment::Left,
+        font,
+        foreground,
+        background,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+    use crate::event::{Button, Modifiers};
+    use crate::testing::font;
+
+    fn table() -> Table {
+        let mut table = Table::new(vec![Column::new("name"), Column::new("size")]);
+        for idx in 0..10 {
+            table.push_row([format!("file {idx}"), format!("{}", idx * 100)]);
+        }
+        table
+    }
+
+    fn press(key: Key) -> Event {
+        Event::KeyPress {
+            key,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn cells() {
+        let mut table = table();
+        table.push_row(["only a name"]);
+        assert_eq!(table.rows(), 11);
+        assert_eq!(table.cell(3, 1), Some("300"));
+        assert_eq!(table.cell(10, 1), Some(""));
+        assert_eq!(table.cell(11, 0), None);
+        assert_eq!(table.cell(0, 2), None);
+    }
+
+    #[test]
+    fn binding() {
+        let binding = TableBinding::<Vec<u32>> {
+            rows: Vec::len,
+            cell: |data, row, column, cell| match column {
+                0 => cell.push_str("file"),
+                _ => cell.push_str(&data[row].to_string()),
+            },
+        };
+        let mut table = table();
+        table.select(Some(5));
+        table.read(&binding, &vec![7, 8, 9, 10, 11, 12]);
+        assert_eq!(table.rows(), 6);
+        assert_eq!(table.cell(2, 1), Some("9"));
+        assert_eq!(table.selected(), Some(5));
+
+        // The selected row went away.
+        table.read(&binding, &vec![1]);
+        assert_eq!(table.cell(0, 0), Some("file"));
+        assert_eq!(table.cell(1, 0), None);
+        assert_eq!(table.selected(), None);
+    }
+
+    #[test]
+    fn column_widths() {
+        let font = font();
+        let mut table = table().with_spacing(3);
+        table.columns[1].width = ColumnWidth::Max(4);
+        let size = table.measure(&font);
+        // The header counts as well.
+        let name = (0..10)
+            .map(|idx| font.determine_width(&format!("file {idx}")) as u32)
+            .fold(font.determine_width("name") as u32, u32::max);
+        assert_eq!(table.widths, [name, 4]);
+        assert_eq!(size.width, name + 3 + 4);
+        assert_eq!(
+            size.height,
+            header_height(&font) + 10 * font.height() as u32
+        );
+    }
+
+    #[test]
+    fn selection_follows_keys() {
+        let font = font();
+        let mut table = table();
+        assert!(table.handle_event(&press(Key::Down), &font, 0, 4));
+        assert_eq!(table.selected(), Some(0));
+        table.handle_event(&press(Key::PageDown), &font, 0, 4);
+        assert_eq!(table.selected(), Some(4));
+        assert_eq!(table.scroll_to_selection(0, 4), 1);
+        table.handle_event(&press(Key::End), &font, 0, 4);
+        table.handle_event(&press(Key::Down), &font, 0, 4);
+        assert_eq!(table.selected(), Some(9));
+        assert_eq!(table.scroll_to_selection(0, 4), 6);
+        table.handle_event(&press(Key::Home), &font, 6, 4);
+        assert_eq!(table.scroll_to_selection(6, 4), 0);
+        assert!(!table.handle_event(&press(Key::Left), &font, 0, 4));
+
+        // A press below the last row clears the selection.
+        let click = |y| Event::PointerPress {
+            x: 0,
+            y,
+            button: Button::Left,
+        };
+        let y = header_height(&font) + font.height() as u32 * 2;
+        table.handle_event(&click(y), &font, 6, 4);
+        assert_eq!(table.selected(), Some(8));
+        table.handle_event(&click(y * 4), &font, 6, 4);
+        assert_eq!(table.selected(), None);
+    }
+
+    #[test]
+    fn no_columns() {
+        let font = font();
+        let mut table = Table::new(Vec::new());
+        table.push_row([""]);
+        assert_eq!(table.rows(), 1);
+        assert_eq!(table.measure(&font).width, 0);
+        let mut block = Block::new(20, 40, [0xff; 4]);
+        table.draw(&mut block.canvas(), &font, [0x00; 4], [0xff; 4], 0);
+    }
+}
@@ -0,0 +1,451 @@
+use std::ops::Range;
+
+use fleck::Font;
+
+use super::text_input::{
+    draw_cursor, draw_glyphs, floor_boundary, index_at, next_boundary, prev_boundary, InputResponse,
+};
+use super::WrappedText;
+use crate::block::Canvas;
+use crate::event::{Event, Key, Modifiers};
+use crate::Pixel;
+
+/// Editable text that spans multiple lines, built on a [`WrappedText`].
+///
+/// The cursor moves along the wrapped lines. Moving up and down keeps the cursor at the same
+/// horizontal position in pixels, as far as the lines allow. Edits only rewrap the text from the
+/// changed line onward, see [`WrappedText::replace_range`].
+///
+/// Like for a [`TextInput`](super::TextInput), the cursor and the ends of the selection are byte
+/// indices into the text, and always lie on `char` boundaries.
+#[derive(Debug, Default, Clone)]
+pub struct TextEditor {
+    wrapped: WrappedText,
+    cursor: usize,
+    /// The end of the selection that stays put while the cursor moves.
+    anchor: Option<usize>,
+    /// The horizontal position in pixels that moving up and down tries to keep.
+    column: Option<u32>,
+}
+
+impl TextEditor {
+    /// Creates a new [`TextEditor`] with the cursor placed at the end of the `wrapped` text.
+    pub fn new(wrapped: WrappedText) -> Self {
+        Self {
+            cursor: wrapped.text().len(),
+            wrapped,
+            anchor: None,
+            column: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.wrapped.text()
+    }
+
+    pub fn wrapped(&self) -> &WrappedText {
+        &self.wrapped
+    }
+
+    /// Replace the text of this [`TextEditor`].
+    ///
+    /// Only the part that differs from the current text is replaced, so the lines before it do
+    /// not need to be rewrapped. The cursor and selection are kept where they were as far as
+    /// possible. A cursor at the end of the old text stays at the end of the new text.
+    pub fn set_text(&mut self, text: &str, font: &Font) {
+        if self.text() == text {
+            return;
+        }
+        let at_end = self.cursor == self.text().len();
+        self.wrapped.set_text(text, font);
+
+        let text = self.wrapped.text();
+        self.cursor = match at_end {
+            true => text.len(),
+            false => floor_boundary(text, self.cursor),
+        };
+        self.anchor = self.anchor.map(|anchor| floor_boundary(text, anchor));
+        self.column = None;
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Place the cursor at byte index `cursor` and clear the selection.
+    ///
+    /// An index that does not lie on a `char` boundary is moved back to the previous one.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.move_to(floor_boundary(self.text(), cursor), false);
+    }
+
+    /// Returns the byte range of the selected text, if anything is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&anchor| anchor != self.cursor)?;
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Select the text within the byte `range`, placing the cursor at its end.
+    pub fn select(&mut self, range: Range<usize>) {
+        self.anchor = Some(floor_boundary(self.text(), range.start));
+        self.cursor = floor_boundary(self.text(), range.end);
+        self.column = None;
+    }
+
+    pub fn selected_text(&self) -> &str {
+        self.selection().map_or("", |range| &self.text()[range])
+    }
+
+    /// Insert `s` at the cursor, replacing the selection if there is one.
+    pub fn insert(&mut self, s: &str, font: &Font) {
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+        self.replace(range, s, font);
+    }
+
+    /// Delete the selection, or the `char` before the cursor if nothing is selected.
+    pub fn delete_backward(&mut self, font: &Font) {
+        let range = self
+            .selection()
+            .unwrap_or(prev_boundary(self.text(), self.cursor)..self.cursor);
+        self.replace(range, "", font);
+    }
+
+    /// Delete the selection, or the `char` after the cursor if nothing is selected.
+    pub fn delete_forward(&mut self, font: &Font) {
+        let range = self
+            .selection()
+            .unwrap_or(self.cursor..next_boundary(self.text(), self.cursor));
+        self.replace(range, "", font);
+    }
+
+    /// Move the cursor one `char` to the left.
+    ///
+    /// When `select` is set, the selection is extended. Otherwise, an existing selection is
+    /// collapsed to its start.
+    pub fn move_left(&mut self, select: bool) {
+        let target = match self.selection() {
+            Some(range) if !select => range.start,
+            _ => prev_boundary(self.text(), self.cursor),
+        };
+        self.move_to(target, select);
+    }
+
+    /// Move the cursor one `char` to the right.
+    ///
+    /// When `select` is set, the selection is extended. Otherwise, an existing selection is
+    /// collapsed to its end.
+    pub fn move_right(&mut self, select: bool) {
+        let target = match self.selection() {
+            Some(range) if !select => range.end,
+            _ => next_boundary(self.text(), self.cursor),
+        };
+        self.move_to(target, select);
+    }
+
+    /// Move the cursor to the line above, keeping its horizontal position. On the first line, the
+    /// cursor moves to the start of the text.
+    ///
+    /// When `select` is set, the selection is extended.
+    pub fn move_up(&mut self, select: bool, font: &Font) {
+        let line = self.cursor_line();
+        self.move_vertically(line.checked_sub(1), select, font);
+    }
+
+    /// Move the cursor to the line below, keeping its horizontal position. On the last line, the
+    /// cursor moves to the end of the text.
+    ///
+    /// When `select` is set, the selection is extended.
+    pub fn move_down(&mut self, select: bool, font: &Font) {
+        let line = self.cursor_line() + 1;
+        let below = (line < self.wrapped.lines_count()).then_some(line);
+        self.move_vertically(below, select, font);
+    }
+
+    /// Move the cursor to the start of its line, extending the selection if `select` is set.
+    pub fn move_home(&mut self, select: bool) {
+        let target = self.line_range(self.cursor_line()).start;
+        self.move_to(target, select);
+    }
+
+    /// Move the cursor to the end of its line, extending the selection if `select` is set.
+    pub fn move_end(&mut self, select: bool) {
+        let target = self.line_range(self.cursor_line()).end;
+        self.move_to(target, select);
+    }
+
+    /// Apply an [`Event`] to this [`TextEditor`].
+    ///
+    /// Text and editing keys edit the text, and Enter starts a new line. The arrow keys, Home,
+    /// and End move the cursor along the wrapped lines (and with shift held, extend the
+    /// selection). With control held, Home and End move to the start and end of the whole text,
+    /// and Control-A selects everything. A pointer press places the cursor at the pressed
+    /// position, which is expected to be relative to the top left corner of the text area.
+    pub fn handle_event(&mut self, event: &Event, font: &Font) -> InputResponse {
+        match *event {
+            Event::Text(ch) if !ch.is_control() => {
+                self.insert(ch.encode_utf8(&mut [0; 4]), font);
+                InputResponse::Edited
+            }
+            Event::KeyPress {
+                key: Key::Enter, ..
+            } => {
+                self.insert("\n", font);
+                InputResponse::Edited
+            }
+            Event::KeyPress {
+                key: key @ (Key::Backspace | Key::Delete),
+                ..
+            } => {
+                let len = self.text().len();
+                match key {
+                    Key::Backspace => self.delete_backward(font),
+                    _ => self.delete_forward(font),
+                }
+                // At either end of the text, the key is still ours, even if nothing changes.
+                match self.text().len() == len {
+                    true => InputResponse::Moved,
+                    false => InputResponse::Edited,
+                }
+            }
+            Event::KeyPress {
+                key,
+                modifiers: Modifiers { shift, control, .. },
+            } => {
+                match key {
+                    Key::Left => self.move_left(shift),
+                    Key::Right => self.move_right(shift),
+                    Key::Up => self.move_up(shift, font),
+                    Key::Down => self.move_down(shift, font),
+                    Key::Home if control => self.move_to(0, shift),
+                    Key::End if control => self.move_to(self.text().len(), shift),
+                    Key::Home => self.move_home(shift),
+                    Key::End => self.move_end(shift),
+                    Key::Char('a') if control => self.select(0..self.text().len()),
+                    _ => return InputResponse::Ignored,
+                }
+                InputResponse::Moved
+            }
+            Event::PointerPress { x, y, .. } => {
+                let last = self.wrapped.lines_count().saturating_sub(1);
+                let line = usize::min(y as usize / font.height(), last);
+                let range = self.line_range(line);
+                let index = range.start + index_at(&self.text()[range], x, font);
+                self.set_cursor(index);
+                InputResponse::Moved
+            }
+            _ => InputResponse::Ignored,
+        }
+    }
+
+    /// Returns the vertical scroll, starting from `scroll`, that keeps the line of the cursor
+    /// within `height` pixels.
+    pub(crate) fn scroll_into_view(&self, scroll: u32, height: u32, font: &Font) -> u32 {
+        let line_height = font.height() as u32;
+        let top = self.cursor_line() as u32 * line_height;
+        if top < scroll {
+            top
+        } else if top + line_height > scroll + height {
+            (top + line_height).saturating_sub(height)
+        } else {
+            scroll
+        }
+    }
+
+    /// Rewrap the text to `maxwidth`.
+    ///
+    /// See [`WrappedText::rewrap`].
+    pub(crate) fn rewrap(&mut self, maxwidth: Option<u32>, font: &Font) {
+        self.wrapped.rewrap(maxwidth, font);
+    }
+
+    /// Draw the wrapped lines, selection, and cursor onto the `canvas`. The selection and cursor
+    /// are only drawn when `focused` is set.
+    pub(crate) fn draw(
+        &self,
+        canvas: &mut Canvas,
+        font: &Font,
+        foreground: Pixel,
+        background: Pixel,
+        focused: bool,
+    ) {
+        let selection = self.selection().filter(|_| focused);
+        let height = font.height() as u32;
+        let colors = (foreground, background);
+        let rows = canvas.visible_rows();
+        let first = (rows.start / height as i64) as usize;
+        for (idx, range) in self.wrapped.line_ranges_from(first).enumerate() {
+            let y = (first + idx) as u32 * height;
+            if y as i64 >= rows.end {
+                break;
+            }
+            draw_glyphs(
+                canvas,
+                font,
+                self.text(),
+                range,
+                (0, y),
+                selection.clone(),
+                colors,
+            );
+        }
+
+        if focused {
+            let line = self.cursor_line();
+            let (x, y) = (self.cursor_x(line, font), line as u32 * height);
+            draw_cursor(canvas, x as i64, y, height, foreground);
+        }
+    }
+
+    /// Returns the byte range of the wrapped line at index `line`.
+    fn line_range(&self, line: usize) -> Range<usize> {
+        self.wrapped.line_ranges().nth(line).unwrap_or_default()
+    }
+
+    /// Returns the index of the wrapped line the cursor is on.
+    ///
+    /// Where a line is broken without any whitespace, the cursor at the break is placed at the
+    /// start of the next line.
+    fn cursor_line(&self) -> usize {
+        self.wrapped
+            .line_ranges()
+            .take_while(|range| range.start <= self.cursor)
+            .count()
+            .saturating_sub(1)
+    }
+
+    /// Returns the horizontal position of the cursor in pixels, given that it is on `line`.
+    fn cursor_x(&self, line: usize, font: &Font) -> u32 {
+        let start = self.line_range(line).start.min(self.cursor);
+        font.determine_width(&self.text()[start..self.cursor]) as u32
+    }
+
+    /// Move the cursor to the line at index `target`, or to the start or end of the text if
+    /// there is no such line.
+    fn move_vertically(&mut self, target: Option<usize>, select: bool, font: &Font) {
+        let column = match self.column {
+            Some(column) => column,
+            None => self.cursor_x(self.cursor_line(), font),
+        };
+        let index = match target {
+            Some(line) => {
+                let range = self.line_range(line);
+                range.start + index_at(&self.text()[range], column, font)
+            }
+            None if self.cursor_line() == 0 => 0,
+            None => self.text().len(),
+        };
+        self.move_to(index, select);
+        self.column = Some(column);
+    }
+
+    fn move_to(&mut self, target: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = target;
+        self.column = None;
+    }
+
+    /// Replace the text in the byte `range` with `s`, and place the cursor behind it.
+    fn replace(&mut self, range: Range<usize>, s: &str, font: &Font) {
+        let start = range.start;
+        self.wrapped.replace_range(range, s, font);
+        self.move_to(start + s.len(), false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: Font = unsafe { std::mem::transmute(*include_bytes!("../../cream12.uf2")) };
+
+    fn press(key: Key, shift: bool) -> Event {
+        Event::KeyPress {
+            key,
+            modifiers: Modifiers {
+                shift,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn editor(text: &str) -> TextEditor {
+        TextEditor::new(WrappedText::new(text.to_string(), 100, &FONT))
+    }
+
+    #[test]
+    fn editing() {
+        let mut editor = editor("hello\nworld");
+        editor.handle_event(&press(Key::Enter, false), &FONT);
+        editor.handle_event(&Event::Text('!'), &FONT);
+        assert_eq!(editor.text(), "hello\nworld\n!");
+        editor.set_cursor(6);
+        editor.handle_event(&press(Key::Backspace, false), &FONT);
+        assert_eq!(editor.text(), "helloworld\n!");
+        assert_eq!(
+            editor.wrapped().lines().collect::<Vec<_>>(),
+            ["helloworld", "!"]
+        );
+
+        editor.handle_event(&press(Key::End, true), &FONT);
+        assert_eq!(editor.selected_text(), "world");
+        editor.handle_event(&press(Key::Delete, false), &FONT);
+        assert_eq!(editor.text(), "hello\n!");
+    }
+
+    #[test]
+    fn wrapped_lines() {
+        let mut editor = editor("the quick brown fox jumps over the lazy dog");
+        let lines: Vec<_> = editor.wrapped().line_ranges().collect();
+        assert!(lines.len() > 2);
+
+        // Home and End follow the wrapped lines, not the unwrapped text.
+        editor.set_cursor(0);
+        editor.handle_event(&press(Key::End, false), &FONT);
+        assert_eq!(editor.cursor(), lines[0].end);
+        editor.handle_event(&press(Key::Right, false), &FONT);
+        editor.handle_event(&press(Key::Right, false), &FONT);
+        assert_eq!(editor.cursor_line(), 1);
+        editor.handle_event(&press(Key::Home, false), &FONT);
+        assert_eq!(editor.cursor(), lines[1].start);
+    }
+
+    #[test]
+    fn vertical_movement_keeps_column() {
+        let mut editor = editor("a long first line\nab\nanother long line");
+        editor.set_cursor(14);
+        let column = editor.cursor_x(0, &FONT);
+
+        // The short line pulls the cursor to its end, but the column is remembered.
+        editor.handle_event(&press(Key::Down, false), &FONT);
+        assert_eq!(editor.cursor(), 20);
+        editor.handle_event(&press(Key::Down, false), &FONT);
+        assert_eq!(editor.cursor_line(), 2);
+        let x = editor.cursor_x(2, &FONT);
+        assert!(x.abs_diff(column) <= 4, "{x} is too far from {column}");
+
+        editor.handle_event(&press(Key::Down, true), &FONT);
+        assert_eq!(editor.cursor(), editor.text().len());
+        editor.handle_event(&press(Key::Up, false), &FONT);
+        editor.handle_event(&press(Key::Up, false), &FONT);
+        editor.handle_event(&press(Key::Up, false), &FONT);
+        assert_eq!(editor.cursor_line(), 0);
+        editor.handle_event(&press(Key::Up, false), &FONT);
+        assert_eq!(editor.cursor(), 0);
+    }
+
+    #[test]
+    fn pointer_press() {
+        let mut editor = editor("first\nsecond");
+        let press = Event::PointerPress {
+            x: 1,
+            y: FONT.height() as u32 + 1,
+            button: crate::event::Button::Left,
+        };
+        editor.handle_event(&press, &FONT);
+        assert_eq!(editor.cursor(), 6);
+    }
+}
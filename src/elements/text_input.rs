@@ -0,0 +1,486 @@
+use std::ops::Range;
+
+use fleck::Font;
+
+use crate::block::Canvas;
+use crate::event::{Event, Key, Modifiers};
+use crate::Pixel;
+
+/// A pair of accessors that bind the value of a [`TextInput`] to a [`String`] in the data `D` of a
+/// [`Panel`](crate::Panel).
+///
+/// When the [`Panel`](crate::Panel) is updated, the `TextInput` takes on the value returned by
+/// `get`. When the `TextInput` is edited, the new value is written through `get_mut`.
+pub struct TextBinding<D> {
+    pub get: fn(&D) -> &str,
+    pub get_mut: fn(&mut D) -> &mut String,
+}
+
+impl<D> Clone for TextBinding<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for TextBinding<D> {}
+
+/// What a [`TextInput`] did in response to an [`Event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputResponse {
+    /// The [`Event`] does not concern the [`TextInput`].
+    Ignored,
+    /// The cursor or selection moved, but the text is unchanged.
+    Moved,
+    /// The text was edited.
+    Edited,
+}
+
+/// An editable single line of text with a cursor and an optional selection.
+///
+/// The cursor and the ends of the selection are byte indices into the text, and always lie on
+/// `char` boundaries.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+    /// The end of the selection that stays put while the cursor moves.
+    anchor: Option<usize>,
+    /// The horizontal scroll in pixels, which keeps the cursor in view.
+    scroll: u32,
+}
+
+impl TextInput {
+    /// Creates a new [`TextInput`] with the cursor placed at the end of the `text`.
+    pub fn new(text: String) -> Self {
+        Self {
+            cursor: text.len(),
+            text,
+            anchor: None,
+            scroll: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text of this [`TextInput`].
+    ///
+    /// The cursor and selection are kept where they were as far as possible. A cursor at the end
+    /// of the old text stays at the end of the new text.
+    pub fn set_text(&mut self, text: &str) {
+        if self.text == text {
+            return;
+        }
+        let at_end = self.cursor == self.text.len();
+        self.text.clear();
+        self.text.push_str(text);
+        self.cursor = match at_end {
+            true => self.text.len(),
+            false => floor_boundary(&self.text, self.cursor),
+        };
+        self.anchor = self.anchor.map(|anchor| floor_boundary(&self.text, anchor));
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Place the cursor at byte index `cursor` and clear the selection.
+    ///
+    /// An index that does not lie on a `char` boundary is moved back to the previous one.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = floor_boundary(&self.text, cursor);
+        self.anchor = None;
+    }
+
+    /// Returns the byte range of the selected text, if anything is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&anchor| anchor != self.cursor)?;
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Select the text within the byte `range`, placing the cursor at its end.
+    pub fn select(&mut self, range: Range<usize>) {
+        self.anchor = Some(floor_boundary(&self.text, range.start));
+        self.cursor = floor_boundary(&self.text, range.end);
+    }
+
+    pub fn selected_text(&self) -> &str {
+        self.selection().map_or("", |range| &self.text[range])
+    }
+
+    /// Insert `s` at the cursor, replacing the selection if there is one.
+    pub fn insert(&mut self, s: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    /// Delete the selection, or the `char` before the cursor if nothing is selected.
+    pub fn delete_backward(&mut self) {
+        if !self.delete_selection() {
+            let start = prev_boundary(&self.text, self.cursor);
+            self.text.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Delete the selection, or the `char` after the cursor if nothing is selected.
+    pub fn delete_forward(&mut self) {
+        if !self.delete_selection() {
+            let end = next_boundary(&self.text, self.cursor);
+            self.text.replace_range(self.cursor..end, "");
+        }
+    }
+
+    /// Move the cursor one `char` to the left.
+    ///
+    /// When `select` is set, the selection is extended. Otherwise, an existing selection is
+    /// collapsed to its start.
+    pub fn move_left(&mut self, select: bool) {
+        let target = match self.selection() {
+            Some(range) if !select => range.start,
+            _ => prev_boundary(&self.text, self.cursor),
+        };
+        self.move_to(target, select);
+    }
+
+    /// Move the cursor one `char` to the right.
+    ///
+    /// When `select` is set, the selection is extended. Otherwise, an existing selection is
+    /// collapsed to its end.
+    pub fn move_right(&mut self, select: bool) {
+        let target = match self.selection() {
+            Some(range) if !select => range.end,
+            _ => next_boundary(&self.text, self.cursor),
+        };
+        self.move_to(target, select);
+    }
+
+    /// Move the cursor to the start of the text, extending the selection if `select` is set.
+    pub fn move_home(&mut self, select: bool) {
+        self.move_to(0, select);
+    }
+
+    /// Move the cursor to the end of the text, extending the selection if `select` is set.
+    pub fn move_end(&mut self, select: bool) {
+        self.move_to(self.text.len(), select);
+    }
+
+    /// Apply an [`Event`] to this [`TextInput`].
+    ///
+    /// Text and editing keys edit the text, the arrow keys and Home and End move the cursor (and
+    /// with shift held, extend the selection), and Control-A selects everything. A pointer press
+    /// places the cursor at the pressed position, which is expected to be relative to the left
+    /// edge of the text area.
+    pub fn handle_event(&mut self, event: &Event, font: &Font) -> InputResponse {
+        match *event {
+            Event::Text(ch) if !ch.is_control() => {
+                self.insert(ch.encode_utf8(&mut [0; 4]));
+                InputResponse::Edited
+            }
+            Event::KeyPress {
+                key: key @ (Key::Backspace | Key::Delete),
+                ..
+            } => {
+                let len = self.text.len();
+                match key {
+                    Key::Backspace => self.delete_backward(),
+                    _ => self.delete_forward(),
+                }
+                // At either end of the text, the key is still ours, even if nothing changes.
+                match self.text.len() == len {
+                    true => InputResponse::Moved,
+                    false => InputResponse::Edited,
+                }
+            }
+            Event::KeyPress {
+                key,
+                modifiers: Modifiers { shift, control, .. },
+            } => {
+                match key {
+                    Key::Left => self.move_left(shift),
+                    Key::Right => self.move_right(shift),
+                    Key::Home => self.move_home(shift),
+                    Key::End => self.move_end(shift),
+                    Key::Char('a') if control => self.select(0..self.text.len()),
+                    _ => return InputResponse::Ignored,
+                }
+                InputResponse::Moved
+            }
+            Event::PointerPress { x, .. } => {
+                let index = index_at(&self.text, x + self.scroll, font);
+                self.set_cursor(index);
+                InputResponse::Moved
+            }
+            _ => InputResponse::Ignored,
+        }
+    }
+
+    /// Adjust the horizontal scroll such that the cursor is visible within `width` pixels.
+    pub(crate) fn scroll_into_view(&mut self, width: u32, font: &Font) {
+        let cursor_x = font.determine_width(&self.text[..self.cursor]) as u32;
+        let text_width = font.determine_width(&self.text) as u32 + 1;
+        // Don't leave empty room on the right when the text would fit.
+        self.scroll = self.scroll.min(text_width.saturating_sub(width));
+        if cursor_x < self.scroll {
+            self.scroll = cursor_x;
+        } else if cursor_x + 1 > self.scroll + width {
+            self.scroll = cursor_x + 1 - width;
+        }
+    }
+
+    /// Returns the width in pixels this [`TextInput`] needs to show its text and the cursor behind
+    /// it.
+    pub(crate) fn natural_width(&self, font: &Font) -> u32 {
+        font.determine_width(&self.text) as u32 + 1
+    }
+
+    /// Draw the text, selection, and cursor onto the `canvas`, taking the horizontal scroll into
+    /// account. The selection and cursor are only drawn when `focused` is set.
+    pub(crate) fn draw(
+        &self,
+        canvas: &mut Canvas,
+        font: &Font,
+        foreground: Pixel,
+        background: Pixel,
+        focused: bool,
+    ) {
+        let selection = self.selection().filter(|_| focused);
+        let all = 0..self.text.len();
+        let x0 = -(self.scroll as i64);
+        let colors = (foreground, background);
+        draw_glyphs(canvas, font, &self.text, all, (x0, 0), selection, colors);
+
+        if focused {
+            let x = font.determine_width(&self.text[..self.cursor]) as i64 - self.scroll as i64;
+            let height = canvas.height();
+            draw_cursor(canvas, x, 0, height, foreground);
+        }
+    }
+
+    fn move_to(&mut self, target: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = target;
+    }
+
+    /// Delete the selected text, if any, and return whether anything was deleted.
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        match selection {
+            Some(range) => {
+                self.cursor = range.start;
+                self.text.replace_range(range, "");
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Returns the byte index of the `char` boundary in `text` closest to the horizontal position `x`.
+pub(super) fn index_at(text: &str, x: u32, font: &Font) -> usize {
+    let mut left = 0;
+    for (idx, ch) in text.char_indices() {
+        let width = font.glyph(ch).map_or(0, |glyph| glyph.width) as u32;
+        if x < left + width / 2 {
+            return idx;
+        }
+        left += width;
+    }
+    text.len()
+}
+
+pub(super) fn floor_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+pub(super) fn prev_boundary(text: &str, index: usize) -> usize {
+    text[..index]
+        .char_indices()
+        .next_back()
+        .map_or(0, |(idx, _)| idx)
+}
+
+pub(super) fn next_boundary(text: &str, index: usize) -> usize {
+    text[index..]
+        .chars()
+        .next()
+        .map_or(index, |ch| index + ch.len_utf8())
+}
+
+/// Draw the `char`s of `text` within the byte `range` onto the `canvas`, starting at the position
+/// `x0`, `y0`. The parts that fall outside of the `canvas` are clipped.
+///
+/// The `char`s that lie within the `selection` are drawn with the `foreground` and `background`
+/// of the `colors` swapped.
+pub(super) fn draw_glyphs(
+    canvas: &mut Canvas,
+    font: &Font,
+    text: &str,
+    range: Range<usize>,
+    (x0, y0): (i64, u32),
+    selection: Option<Range<usize>>,
+    (foreground, background): (Pixel, Pixel),
+) {
+    let columns = canvas.visible_columns();
+    let mut x0 = x0;
+    for (idx, ch) in text[range.clone()].char_indices() {
+        if x0 >= columns.end {
+            break;
+        }
+        let idx = range.start + idx;
+        let Some(glyph) = font.glyph(ch) else {
+            continue;
+        };
+        let glyph_width = glyph.width as i64;
+        if x0 + glyph_width <= columns.start {
+            x0 += glyph_width;
+            continue;
+        }
+        // The background behind unselected text has already been painted, and painting it again
+        // would blend a translucent background twice.
+        let selected = selection.as_ref().is_some_and(|range| range.contains(&idx));
+        let (fg, bg) = match selected {
+            true => (background, Some(foreground)),
+            false => (foreground, None),
+        };
+        let blend_mode = canvas.blend_mode();
+        for (y, glyph_row) in glyph.enumerate() {
+            let Some((start, row)) = canvas.row_mut(y0 as i64 + y as i64) else {
+                continue;
+            };
+            for (xg, cell) in glyph_row.enumerate() {
+                let x = x0 + xg as i64 - start;
+                let Some(pixel) = usize::try_from(x).ok().and_then(|x| row.get_mut(x)) else {
+                    continue;
+                };
+                match (cell, bg) {
+                    (true, _) => blend_mode.apply(pixel, fg),
+                    (false, Some(bg)) => blend_mode.apply(pixel, bg),
+                    (false, None) => {}
+                }
+            }
+        }
+        x0 += glyph_width;
+    }
+}
+
+/// Draw a cursor of `height` pixels onto the `canvas`, with its top at the position `x`, `y0`.
+pub(super) fn draw_cursor(canvas: &mut Canvas, x: i64, y0: u32, height: u32, color: Pixel) {
+    canvas.view(x, y0 as i64, 1, height).fill(color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: Font = unsafe { std::mem::transmute(*include_bytes!("../../cream12.uf2")) };
+
+    fn press(key: Key, shift: bool) -> Event {
+        Event::KeyPress {
+            key,
+            modifiers: Modifiers {
+                shift,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn editing() {
+        let mut input = TextInput::new("héllo".to_string());
+        assert_eq!(input.cursor(), 6);
+        input.handle_event(&press(Key::Left, false), &FONT);
+        input.handle_event(&press(Key::Left, false), &FONT);
+        input.handle_event(&press(Key::Left, false), &FONT);
+        input.handle_event(&press(Key::Left, false), &FONT);
+        assert_eq!(input.cursor(), 1);
+        assert_eq!(
+            input.handle_event(&press(Key::Delete, false), &FONT),
+            InputResponse::Edited
+        );
+        assert_eq!(input.text(), "hllo");
+        input.handle_event(&Event::Text('e'), &FONT);
+        input.handle_event(&press(Key::End, false), &FONT);
+        input.handle_event(&press(Key::Backspace, false), &FONT);
+        assert_eq!(input.text(), "hell");
+        input.handle_event(&press(Key::Home, false), &FONT);
+        assert_eq!(
+            input.handle_event(&press(Key::Backspace, false), &FONT),
+            InputResponse::Moved
+        );
+        assert_eq!(
+            input.handle_event(&Event::Text('\n'), &FONT),
+            InputResponse::Ignored
+        );
+        assert_eq!(
+            input.handle_event(&press(Key::Enter, false), &FONT),
+            InputResponse::Ignored
+        );
+        assert_eq!(input.text(), "hell");
+    }
+
+    #[test]
+    fn selection() {
+        let mut input = TextInput::new("hello world".to_string());
+        input.handle_event(&press(Key::Home, true), &FONT);
+        assert_eq!(input.selected_text(), "hello world");
+        input.handle_event(&press(Key::Right, false), &FONT);
+        assert_eq!((input.selection(), input.cursor()), (None, 11));
+
+        input.set_cursor(5);
+        input.handle_event(&press(Key::Left, true), &FONT);
+        input.handle_event(&press(Key::Left, true), &FONT);
+        assert_eq!(input.selected_text(), "lo");
+        input.handle_event(&Event::Text('p'), &FONT);
+        assert_eq!(input.text(), "help world");
+        assert_eq!(input.selection(), None);
+
+        let select_all = Event::KeyPress {
+            key: Key::Char('a'),
+            modifiers: Modifiers {
+                control: true,
+                ..Default::default()
+            },
+        };
+        input.handle_event(&select_all, &FONT);
+        input.handle_event(&press(Key::Delete, false), &FONT);
+        assert_eq!(input.text(), "");
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut input = TextInput::new("0123456789".repeat(4));
+        let width = 50;
+        input.scroll_into_view(width, &FONT);
+        let text_width = FONT.determine_width(input.text()) as u32;
+        assert_eq!(input.scroll, text_width + 1 - width);
+
+        input.handle_event(&press(Key::Home, false), &FONT);
+        input.scroll_into_view(width, &FONT);
+        assert_eq!(input.scroll, 0);
+
+        // A press places the cursor at the nearest boundary, taking the scroll into account.
+        input.scroll = 30;
+        let press = Event::PointerPress {
+            x: 0,
+            y: 0,
+            button: crate::event::Button::Left,
+        };
+        input.handle_event(&press, &FONT);
+        let cursor_x = FONT.determine_width(&input.text()[..input.cursor()]) as i64;
+        let glyph_width = FONT.glyph('0').unwrap().width as i64;
+        assert!((cursor_x - 30).abs() <= glyph_width / 2);
+    }
+}
@@ -0,0 +1,489 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use fleck::Font;
+
+use super::{Constraints, Content, Dimensions, Element};
+use crate::error::LayoutError;
+
+/// How tall the rows of a [`VirtualList`] are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowHeight {
+    /// Every row is laid out at exactly this many pixels tall, including its padding.
+    ///
+    /// This is the cheapest choice, since the position of any row follows from its index.
+    Fixed(u32),
+    /// Every row is as tall as it turns out to be once it is laid out. A row that was never in
+    /// view is assumed to be this many pixels tall.
+    ///
+    /// The heights of the rows that were laid out are kept by their index, so the
+    /// [`Element::content_size`] becomes more accurate the more rows have been in view.
+    Estimated(u32),
+}
+
+/// A list of rows that are built from the data `D` of a [`Panel`](crate::Panel), of which only
+/// the rows within view are built, laid out and drawn.
+///
+/// This makes for a list of a hundred thousand rows that is as cheap as one of a few rows. The
+/// number of rows is asked from the data through `rows`, and a row is built by the `row` renderer
+/// once it scrolls into view. It is kept, along with its state, for as long as it stays in view,
+/// and dropped once it leaves. Like any [`Element`], a row can follow changes to its item through
+/// an update function, which may capture the index of the item, see [`Element::with_update`].
+///
+/// The `scroll` of the [`Element`] that holds a `VirtualList` is in pixels, as usual. The rows in
+/// view are the children of that `Element`, starting from the [`VirtualList::first`] row, so that
+/// they can be hit, focused and handle events like the children of a [`Content::Stack`]. Rows that
+/// come into view are built when the `Panel` is updated.
+pub struct VirtualList<D> {
+    rows: fn(&D) -> usize,
+    row: fn(data: &D, index: usize, font: &Rc<Font>) -> Element<D>,
+    height: RowHeight,
+    /// The number of rows as of the last update.
+    count: usize,
+    /// The index of the row that the first of the `children` shows.
+    first: usize,
+    /// The rows in view.
+    pub(super) children: Vec<Element<D>>,
+    /// The heights of the rows that were laid out, for [`RowHeight::Estimated`].
+    heights: Heights,
+}
+
+impl<D> VirtualList<D> {
+    /// Creates a new [`VirtualList`] that takes the number of its `rows` from the data, and builds
+    /// the rows in view through `row`.
+    pub fn new(
+        rows: fn(&D) -> usize,
+        row: fn(data: &D, index: usize, font: &Rc<Font>) -> Element<D>,
+        height: RowHeight,
+    ) -> Self {
+        Self {
+            rows,
+            row,
+            height,
+            count: 0,
+            first: 0,
+            children: Vec::new(),
+            heights: Heights::default(),
+        }
+    }
+
+    /// Returns the number of rows, as of the last update.
+    pub fn rows(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the index of the first row in view, which is the first child of the [`Element`].
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    /// Returns the row at `index`, if it is in view.
+    pub fn row(&self, index: usize) -> Option<&Element<D>> {
+        self.children.get(index.checked_sub(self.first)?)
+    }
+
+    /// Returns the distance from the top of the list to the row at `index`, which is how far the
+    /// [`Element`] is scrolled to bring that row to the top. For [`RowHeight::Estimated`], this
+    /// assumes the estimate for every row above it that was never laid out.
+    ///
+    /// Returns [`None`] if there is no such row.
+    pub fn row_offset(&self, index: usize) -> Option<u32> {
+        (index < self.count).then(|| self.offset(index))
+    }
+
+    /// Drop all rows, so that the rows in view are built again at the next update. Any heights
+    /// that were measured are forgotten as well.
+    ///
+    /// This is how to pick up changes to the items that the rows do not follow by themselves.
+    pub fn invalidate(&mut self) {
+        self.children.clear();
+        self.heights.clear();
+    }
+
+    /// Returns the height of the row at `index`, as it was laid out or estimated.
+    fn row_height(&self, index: usize) -> u32 {
+        match self.height {
+            RowHeight::Fixed(height) => height,
+            RowHeight::Estimated(height) => self.heights.measured[index].unwrap_or(height),
+        }
+    }
+
+    /// Returns the distance from the top of the list to the row at `index`.
+    fn offset(&self, index: usize) -> u32 {
+        match self.height {
+            RowHeight::Fixed(height) => index as u32 * height,
+            RowHeight::Estimated(height) => self.heights.offset(index, height),
+        }
+    }
+
+    /// Returns the indices of the rows that lie within the `view`, a range of distances from the
+    /// top of the list. A row without any height is in view when its top is.
+    fn window(&self, view: Range<u32>) -> Range<usize> {
+        let estimate = match self.height {
+            RowHeight::Fixed(height) => {
+                let Some(start) = view.start.checked_div(height) else {
+                    return 0..0;
+                };
+                let end = view.end.div_ceil(height);
+                return (start as usize).min(self.count)..(end as usize).min(self.count);
+            }
+            RowHeight::Estimated(estimate) => estimate,
+        };
+        let (mut start, mut top) = self.heights.above(view.start, estimate);
+        // Rows without any height that lie right at the top of the view are in it.
+        if top == view.start {
+            while start > 0 && self.row_height(start - 1) == 0 {
+                start -= 1;
+            }
+        }
+        let mut end = start;
+        while end < self.count && top < view.end {
+            top += self.row_height(end);
+            end += 1;
+        }
+        start..end
+    }
+
+    /// Take on the number of rows of the `data`, build the rows that came within the `view` and
+    /// drop those that left it.
+    ///
+    /// Returns whether any row was built or dropped.
+    fn read(&mut self, data: &D, view: Range<u32>, font: &Rc<Font>) -> bool {
+        self.count = (self.rows)(data);
+        if let RowHeight::Estimated(_) = self.height {
+            self.heights.resize(self.count);
+        }
+        let window = self.window(view);
+        let kept = self.first..self.first + self.children.len();
+        if window == kept {
+            return false;
+        }
+
+        // The rows that are in view both before and after are kept where they are.
+        let start = window.start.clamp(kept.start, kept.end) - kept.start;
+        let end = window.end.clamp(kept.start, kept.end) - kept.start;
+        self.children.truncate(end);
+        self.children.drain(..start.min(end));
+        let (keep_start, keep_end) = match start < end {
+            true => (kept.start + start, kept.start + end),
+            false => (window.start, window.start),
+        };
+        let before: Vec<_> = (window.start..keep_start)
+            .map(|idx| (self.row)(data, idx, font))
+            .collect();
+        self.children.splice(..0, before);
+        self.children
+            .extend((keep_end..window.end).map(|idx| (self.row)(data, idx, font)));
+        self.first = window.start;
+        true
+    }
+
+    /// Lay out the rows in view within the `maxwidth`, and remember their heights.
+    ///
+    /// Returns the size of the whole list, where the width is that of the widest row in view.
+    pub(super) fn measure(&mut self, maxwidth: Option<u32>) -> Result<Dimensions, LayoutError> {
+        let room = match self.height {
+            RowHeight::Fixed(height) => {
+                Constraints::new(maxwidth, Some(height)).with_minheight(height)
+            }
+            RowHeight::Estimated(_) => Constraints::new(maxwidth, None),
+        };
+        let mut width = 0;
+        for (idx, child) in self.children.iter_mut().enumerate() {
+            child.layout(room).map_err(|err| err.within(idx))?;
+            let size = child.overall_size();
+            width = width.max(size.width);
+            if let RowHeight::Estimated(estimate) = self.height {
+                self.heights.set(self.first + idx, size.height, estimate);
+            }
+        }
+        Ok(Dimensions {
+            width,
+            height: self.offset(self.count),
+        })
+    }
+
+    /// Place the rows in view one below the other, at their distance from the top of the list.
+    /// Must be called after [`VirtualList::measure`].
+    pub(super) fn arrange(&mut self) {
+        let mut top = self.offset(self.first);
+        for child in &mut self.children {
+            child.position = (0, top as i64);
+            top += match self.height {
+                RowHeight::Fixed(height) => height,
+                RowHeight::Estimated(_) => child.overall_size().height,
+            };
+        }
+    }
+}
+
+/// The heights of the rows of a list with a [`RowHeight::Estimated`], where every row that was
+/// never laid out is assumed to be as tall as the estimate.
+///
+/// The differences between the measured heights and the estimate are kept in a Fenwick tree, so
+/// that the distance from the top of the list to any row is found in a number of steps that
+/// grows with the logarithm of the number of rows, rather than with the number of rows itself.
+#[derive(Default)]
+struct Heights {
+    /// The height of every row that was laid out, by its index.
+    measured: Vec<Option<u32>>,
+    /// The sums of the differences with the estimate, where the entry at `idx` covers the rows
+    /// from `idx + 1 - lowest(idx + 1)` up to and including `idx`.
+    tree: Vec<i64>,
+}
+
+/// Returns the lowest bit that is set in `n`.
+fn lowest(n: usize) -> usize {
+    n & n.wrapping_neg()
+}
+
+impl Heights {
+    /// Forget the heights of all rows.
+    fn clear(&mut self) {
+        self.measured.clear();
+        self.tree.clear();
+    }
+
+    /// Grow or shrink to `count` rows. Rows that are added have not been laid out yet.
+    fn resize(&mut self, count: usize) {
+        self.measured.resize(count, None);
+        self.tree.truncate(count);
+        // An entry covers its own row, which adds nothing yet, and the rows of the entries below
+        // it that it spans.
+        while self.tree.len() < count {
+            let idx = self.tree.len();
+            let mut span = 1;
+            let mut sum = 0;
+            while span < lowest(idx + 1) {
+                sum += self.tree[idx - span];
+                span *= 2;
+            }
+            self.tree.push(sum);
+        }
+    }
+
+    /// Remember that the row at `index` was laid out at `height`.
+    fn set(&mut self, index: usize, height: u32, estimate: u32) {
+        let old = self.measured[index].unwrap_or(estimate);
+        self.measured[index] = Some(height);
+        let diff = height as i64 - old as i64;
+        if diff == 0 {
+            return;
+        }
+        let mut idx = index + 1;
+        while idx <= self.tree.len() {
+            self.tree[idx - 1] += diff;
+            idx += lowest(idx);
+        }
+    }
+
+    /// Returns the distance from the top of the list to the row at `index`.
+    fn offset(&self, index: usize, estimate: u32) -> u32 {
+        let mut sum = index as i64 * estimate as i64;
+        let mut idx = index;
+        while idx > 0 {
+            sum += self.tree[idx - 1];
+            idx -= lowest(idx);
+        }
+        sum as u32
+    }
+
+    /// Returns the number of rows whose bottom lies at or above `top`, along with the distance
+    /// from the top of the list to the first row after those.
+    fn above(&self, top: u32, estimate: u32) -> (usize, u32) {
+        let len = self.tree.len();
+        let (mut count, mut sum) = (0, 0);
+        let mut span = len.checked_ilog2().map_or(0, |log| 1 << log);
+        while span > 0 {
+            if count + span <= len {
+                let next = sum + span as i64 * estimate as i64 + self.tree[count + span - 1];
+                if next <= top as i64 {
+                    count += span;
+                    sum = next;
+                }
+            }
+            span /= 2;
+        }
+        (count, sum as u32)
+    }
+}
+
+impl<D> Element<D> {
+    /// Build the rows of the [`VirtualList`] of this [`Element`], if it has one, that are within
+    /// its fill area as it was last laid out, and drop the rest.
+    ///
+    /// Returns whether any row was built or dropped.
+    pub(super) fn read_rows(&mut self, data: &D) -> bool {
+        let top = self.scroll.unwrap_or_default();
+        let view = top..top + self.fill_size().height;
+        let Content::VirtualList(list) = &mut self.content else {
+            return false;
+        };
+        list.read(data, view, &self.style.font)
+    }
+
+    /// Build the rows that came into view of every [`VirtualList`] in this tree since it was last
+    /// updated, which is only known once it has been laid out. The rows that are built are
+    /// updated right away.
+    ///
+    /// Returns whether any row was built or dropped, in which case the tree needs to be laid out
+    /// again.
+    pub(crate) fn read_visible_rows(&mut self, data: &D) -> bool {
+        let mut changed = self.read_rows(data);
+        if changed {
+            self.content
+                .children_mut()
+                .iter_mut()
+                .for_each(|row| row.update(data));
+        }
+        for child in self.content.children_mut() {
+            changed |= child.read_visible_rows(data);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::builder::ElementBuilder;
+    use crate::elements::Alignment;
+    use crate::testing::font;
+    use crate::Panel;
+
+    /// The number of lines of the log, each of which reads its own index.
+    type Data = usize;
+
+    fn line(_: &Data, index: usize, font: &Rc<Font>) -> Element<Data> {
+        let text = Content::Text(format!("line {index}"), Alignment::Left);
+        Element::still(Rc::clone(font), text)
+    }
+
+    /// A row that is as tall as a line for every digit in its index.
+    fn digits(_: &Data, index: usize, font: &Rc<Font>) -> Element<Data> {
+        let height = index.to_string().len() as u32 * 16;
+        line(&0, index, font).with_fixedheight(height)
+    }
+
+    fn panel(row: fn(&Data, usize, &Rc<Font>) -> Element<Data>, height: RowHeight) -> Panel<Data> {
+        let font = font();
+        let elements = Element::virtual_list(|data: &Data| *data, row, &font)
+            .with_row_height(height)
+            .build();
+        let mut panel = Panel::new(elements, [0x00; 4], [0xff; 4], 100_000).unwrap();
+        panel.resize(80, 40);
+        panel.update().unwrap();
+        panel
+    }
+
+    fn list(panel: &Panel<Data>) -> &VirtualList<Data> {
+        let Content::VirtualList(list) = &panel.elements.content else {
+            unreachable!()
+        };
+        list
+    }
+
+    fn texts(panel: &Panel<Data>) -> Vec<&str> {
+        let children = panel.elements.content.children();
+        children
+            .iter()
+            .map(|child| match &child.content {
+                Content::Text(text, _) => text.as_str(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_rows_in_view_are_built() {
+        let mut panel = panel(line, RowHeight::Fixed(16));
+        // The rows are built within the same update that first lays out the list.
+        assert_eq!(texts(&panel), ["line 0", "line 1", "line 2"]);
+        assert_eq!(panel.elements.content_size().height, 100_000 * 16);
+
+        // Mark a row, so that we can tell it is kept rather than built again.
+        panel.elements.content.children_mut()[2].style.background = [0x12, 0x34, 0x56, 0xff];
+        panel.elements.scroll = Some(36);
+        panel.update().unwrap();
+        assert_eq!(texts(&panel), ["line 2", "line 3", "line 4"]);
+        assert_eq!(list(&panel).first(), 2);
+        let kept = list(&panel).row(2).unwrap();
+        assert_eq!(kept.style.background, [0x12, 0x34, 0x56, 0xff]);
+        assert_eq!(list(&panel).row(3).unwrap().position(), (0, 48));
+
+        // A hit lands on the row under the pointer.
+        assert_eq!(panel.hit_test(4, 20).unwrap().path, [1]);
+
+        let last = list(&panel).row_offset(99_999).unwrap();
+        panel.elements.scroll = Some(panel.elements.max_scroll().height);
+        panel.update().unwrap();
+        assert_eq!(last, 99_999 * 16);
+        assert_eq!(texts(&panel), ["line 99997", "line 99998", "line 99999"]);
+        let mut pixels = vec![0; (80 * 40) as usize * 4];
+        panel.draw(&mut pixels).unwrap();
+    }
+
+    #[test]
+    fn heights_are_summed() {
+        // Heights from 0 to 31, mixed up, with every third row left at the estimate.
+        let heights: Vec<Option<u32>> = (0..1000)
+            .map(|idx: u32| (!idx.is_multiple_of(3)).then_some(idx * 7 % 32))
+            .collect();
+        let mut sums = Heights::default();
+        sums.resize(heights.len());
+        for (idx, height) in heights.iter().enumerate() {
+            if let Some(height) = height {
+                sums.set(idx, *height, 16);
+            }
+        }
+        // Measuring a row again replaces its height, and rows that are added again are estimated.
+        sums.set(1, 40, 16);
+        sums.set(1, 20, 16);
+        sums.resize(700);
+        sums.resize(900);
+        let expected = (0..900).map(|idx| match idx {
+            1 => 20,
+            0..700 => heights[idx].unwrap_or(16),
+            _ => 16,
+        });
+
+        let mut top = 0;
+        for (idx, height) in expected.enumerate() {
+            assert_eq!(sums.offset(idx, 16), top);
+            // The rows above any distance are those whose bottom lies at or above it.
+            for distance in [top, top + height / 2] {
+                let count = (0..900)
+                    .filter(|&row| sums.offset(row + 1, 16) <= distance)
+                    .count();
+                assert_eq!(sums.above(distance, 16), (count, sums.offset(count, 16)));
+            }
+            top += height;
+        }
+        assert_eq!(sums.offset(900, 16), top);
+    }
+
+    #[test]
+    fn estimated_heights_are_measured() {
+        let mut panel = panel(digits, RowHeight::Estimated(16));
+        assert_eq!(list(&panel).rows(), 100_000);
+        assert_eq!(panel.elements.content_size().height, 100_000 * 16);
+
+        // The rows from ten onwards turn out to be taller once they are laid out, which leaves
+        // less of them in view than estimated.
+        panel.elements.scroll = Some(9 * 16);
+        panel.update().unwrap();
+        assert_eq!(texts(&panel), ["line 9", "line 10"]);
+        assert_eq!(list(&panel).row(10).unwrap().position(), (0, 10 * 16));
+        assert_eq!(panel.elements.content_size().height, (100_000 + 2) * 16);
+        assert_eq!(list(&panel).row_offset(12), Some(14 * 16));
+
+        // Fewer rows drop those that were in view beyond the end, and the scroll is brought back
+        // to the last rows that are left.
+        *panel.data_mut() = 10;
+        panel.update().unwrap();
+        assert_eq!(panel.elements.scroll, Some(10 * 16 - 40));
+        assert_eq!(texts(&panel), ["line 7", "line 8", "line 9"]);
+        let mut pixels = vec![0; (80 * 40) as usize * 4];
+        panel.draw(&mut pixels).unwrap();
+    }
+}
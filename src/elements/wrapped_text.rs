@@ -1,3 +1,6 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use fleck::Font;
 
 /// A wrapper for a [`String`] where its contents are guaranteed to be wrapped at time of use.
@@ -8,10 +11,34 @@ use fleck::Font;
 /// # Note
 ///
 /// No actual wrapping of the internal string takes place at time of [creation](WrappedText::new)
-/// or when [rewrapped](WrappedText::rewrap). In fact, the internal string is not mutate over the
-/// lifetime of [`WrappedText`].
+/// or when [rewrapped](WrappedText::rewrap). The internal string is only mutated through
+/// [`WrappedText::replace_range`].
 #[derive(Debug, Default, Clone)]
-pub struct WrappedText(String, Vec<usize>);
+pub struct WrappedText {
+    text: String,
+    /// The ends of the lines. The last one always lies at the length of the `text`.
+    breaks: Vec<Break>,
+    /// The width the `text` is currently wrapped to.
+    width: Option<u32>,
+    /// Changes whenever the `text` or its lines change. See [`WrappedText::revision`].
+    revision: u64,
+}
+
+/// The source of [`WrappedText`] revisions, which makes them unique across all instances.
+static REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// The end of a wrapped line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Break {
+    /// The byte index at which the line ends.
+    at: usize,
+    /// The byte index of the `char` that was being scanned when the break was found.
+    ///
+    /// The break can lie before this `char`, since a line is broken at the last whitespace before
+    /// the `char` that overflows it. Everything up to and including this `char` had to be scanned
+    /// to place the break, which makes it the point from which wrapping can pick up again.
+    found: usize,
+}
 
 impl WrappedText {
     /// Creates a new [`WrappedText`] that will be wrapped to the specified `width` and according
@@ -29,35 +56,152 @@ impl WrappedText {
     /// In order to wrap the text to the desired width at a later stage, call
     /// [`WrappedText::rewrap`].
     pub(crate) fn new_without_width(text: String, width: Option<u32>, font: &Font) -> Self {
-        let mut ret = Self(text, Vec::new());
+        let mut ret = Self {
+            text,
+            breaks: Vec::new(),
+            width: None,
+            revision: 0,
+        };
         ret.rewrap(width, font);
         ret
     }
 
+    /// Returns the unwrapped text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the width this [`WrappedText`] is currently wrapped to.
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    /// Returns a number that changes whenever the text or the way it is wrapped changes.
+    ///
+    /// Two [`WrappedText`]s with the same revision hold the same lines, which makes this a cheap
+    /// way to tell whether a large text needs to be redrawn.
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
     /// Rewrap the [`WrappedText`] to the desired width.
     ///
-    /// If `None` is passed as the `maxwidth`, the lines are not wrapped.
+    /// If `None` is passed as the `maxwidth`, the lines are not wrapped. When the text is already
+    /// wrapped to `maxwidth`, nothing needs to be done. Note that this assumes that the text is
+    /// wrapped according to the same [`Font`] as before.
     pub fn rewrap(&mut self, maxwidth: Option<u32>, font: &Font) {
         // TODO: Do this optimization that I had this note for:
         // > TODO: I don't know whether this makes any sense. Never measured it. I like it because
         // > it may prevent two allocations but also, who cares.
 
-        // TODO: Equal starts optimization.
+        if self.width == maxwidth && !self.breaks.is_empty() {
+            return;
+        }
+        self.width = maxwidth;
+        self.revision = REVISION.fetch_add(1, Ordering::Relaxed);
+        self.breaks.clear();
+        self.wrap_from(0, 0, None, font);
+    }
+
+    /// Replace the text of this [`WrappedText`] with `text`.
+    ///
+    /// Where the start of the new text is equal to the old text, the lines are kept as they are,
+    /// and only the rest is rewrapped. Setting the same text again does no work at all.
+    pub fn set_text(&mut self, text: &str, font: &Font) {
+        if self.text == text {
+            return;
+        }
+        let common = self
+            .text
+            .char_indices()
+            .zip(text.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(self.text.len().min(text.len()), |((idx, _), _)| idx);
+        self.replace_range(common..self.text.len(), &text[common..], font);
+    }
+
+    /// Append `s` to the text, and wrap only the lines at the end that are affected by it.
+    pub fn push_str(&mut self, s: &str, font: &Font) {
+        let len = self.text.len();
+        self.replace_range(len..len, s, font);
+    }
+
+    /// Replace the text within the byte `range` with `replace_with`, and rewrap the lines that
+    /// may have changed because of it.
+    ///
+    /// The breaks that were found before the start of the `range` are kept, and wrapping picks up
+    /// from the last of them. So editing near the end of a long text is cheap.
+    ///
+    /// # Panics
+    ///
+    /// If the `range` does not lie on `char` boundaries of the text, this function will panic.
+    pub fn replace_range(&mut self, range: Range<usize>, replace_with: &str, font: &Font) {
+        let keep = self.breaks.partition_point(|br| br.found < range.start);
+        self.text.replace_range(range, replace_with);
+        self.revision = REVISION.fetch_add(1, Ordering::Relaxed);
+        self.breaks.truncate(keep);
+        self.rewrap_tail(font);
+    }
+
+    /// Wrap the text after the last of the current breaks.
+    ///
+    /// The scanning state right after a break was found only depends on the text between the
+    /// break and the `char` it was found at, so it can be reconstructed from there.
+    fn rewrap_tail(&mut self, font: &Font) {
+        let Some(&Break { at, found }) = self.breaks.last() else {
+            self.wrap_from(0, 0, None, font);
+            return;
+        };
+        let ch = self.text[found..]
+            .chars()
+            .next()
+            .expect("a break is found at a char");
+        let start = found + ch.len_utf8();
+        if ch == '\n' {
+            self.wrap_from(start, 0, None, font);
+            return;
+        }
+        // Once a line is broken, its scrap consists of the word that was carried over. If it was
+        // broken at a whitespace, that whitespace is still the last one.
+        let scrapwidth = self.text[at..start]
+            .chars()
+            .map(|ch| font.glyph(ch).map_or(0, |ch| ch.width) as u32)
+            .sum();
+        let last_whitespace = self.text[at..]
+            .starts_with(char::is_whitespace)
+            .then_some(at);
+        self.wrap_from(start, scrapwidth, last_whitespace, font);
+    }
 
-        let Self(text, breaklist) = self;
-        breaklist.clear();
-        let mut scrapwidth = 0u32;
-        let mut wordwidth = 0u32;
-        // FIXME: There may be a bug with a very long unbroken first line because we set it to 0
-        // here. Maybe consider a None here.
-        let mut last_whitespace = None;
-        for (idx, ch) in text.char_indices() {
+    /// Push the breaks for the text from the byte index `start` onward, starting out with the
+    /// provided scanning state.
+    fn wrap_from(
+        &mut self,
+        start: usize,
+        mut scrapwidth: u32,
+        mut last_whitespace: Option<usize>,
+        font: &Font,
+    ) {
+        let Self {
+            text,
+            breaks: breaklist,
+            width: maxwidth,
+            ..
+        } = self;
+        let maxwidth = *maxwidth;
+        // Right after a break, the scrap holds only the current word.
+        let mut wordwidth = scrapwidth;
+        for (idx, ch) in text[start..].char_indices() {
+            let idx = start + idx;
             match ch {
                 '\n' => {
                     scrapwidth = 0;
                     wordwidth = 0;
                     last_whitespace = None; // FIXME: Or None?
-                    breaklist.push(idx)
+                    breaklist.push(Break {
+                        at: idx,
+                        found: idx,
+                    })
                 }
                 ch if maxwidth.is_some() => {
                     if ch.is_whitespace() {
@@ -74,7 +218,7 @@ impl WrappedText {
                                 idx
                             }
                         };
-                        breaklist.push(br);
+                        breaklist.push(Break { at: br, found: idx });
                         wordwidth += glyphwidth;
                         scrapwidth = wordwidth;
                     } else {
@@ -86,26 +230,49 @@ impl WrappedText {
             }
         }
 
-        breaklist.push(text.len());
+        breaklist.push(Break {
+            at: text.len(),
+            found: text.len(),
+        });
     }
 
     /// Returns an iterator over the lines of this [`WrappedText`].
     pub fn lines(&self) -> impl Iterator<Item = &str> {
-        let mut runner = 0;
-        self.1.iter().map(move |&breakpoint| {
-            let a = &self.0[runner..breakpoint];
-            runner = breakpoint;
-            if a.chars().next().is_some_and(|ch| ch.is_whitespace()) {
-                &a[1..]
-            } else {
-                a
+        self.line_ranges().map(|range| &self.text[range])
+    }
+
+    /// Returns an iterator over the byte ranges of the lines of this [`WrappedText`] within the
+    /// unwrapped text.
+    ///
+    /// The whitespace at which a line was broken is not part of any line.
+    pub fn line_ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.line_ranges_from(0)
+    }
+
+    /// Returns an iterator over the byte ranges of the lines of this [`WrappedText`], starting at
+    /// the line with index `first`.
+    ///
+    /// Unlike skipping lines of [`WrappedText::line_ranges`], this does not need to walk past the
+    /// lines before `first`.
+    pub(crate) fn line_ranges_from(&self, first: usize) -> impl Iterator<Item = Range<usize>> + '_ {
+        let first = first.min(self.breaks.len());
+        let mut runner = match first {
+            0 => 0,
+            _ => self.breaks[first - 1].at,
+        };
+        self.breaks[first..].iter().map(move |&Break { at, .. }| {
+            let start = runner;
+            runner = at;
+            match self.text[start..at].chars().next() {
+                Some(ch) if ch.is_whitespace() => start + ch.len_utf8()..at,
+                _ => start..at,
             }
         })
     }
 
     /// Returns the number of wrapped lines in this [`WrappedText`].
     pub fn lines_count(&self) -> usize {
-        self.1.len()
+        self.breaks.len()
     }
 
     /// Return a wrapped [`String`].
@@ -247,4 +414,56 @@ machine.
             rewrapped.lines().collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn replace_range() {
+        let lorem = include_str!("../../examples/lorem.txt");
+        let mut wrapped = WrappedText::new(lorem.to_string(), 300, &FONT);
+        let edits = [
+            (0..0, "Hello "),
+            (40..52, ""),
+            (100..101, "\n"),
+            (
+                200..230,
+                "a very long replacement that spans more than a line",
+            ),
+            (60..61, ""),
+            (5..6, "Supercalifragilisticexpialidocious"),
+        ];
+        for (range, s) in edits {
+            wrapped.replace_range(range, s, &FONT);
+            let fresh = WrappedText::new(wrapped.text().to_string(), 300, &FONT);
+            assert_eq!(wrapped.breaks, fresh.breaks);
+        }
+
+        let len = wrapped.text().len();
+        wrapped.replace_range(len..len, " ipsum", &FONT);
+        assert!(wrapped.wrapped().ends_with(" ipsum"));
+    }
+
+    #[test]
+    fn incremental() {
+        let lorem = include_str!("../../examples/lorem.txt");
+        let (head, tail) = lorem.split_at(400);
+        let mut wrapped = WrappedText::new(head.to_string(), 300, &FONT);
+        for chunk in tail.as_bytes().chunks(37) {
+            wrapped.push_str(std::str::from_utf8(chunk).unwrap(), &FONT);
+        }
+        assert_eq!(
+            wrapped.breaks,
+            WrappedText::new(lorem.to_string(), 300, &FONT).breaks
+        );
+
+        let changed = lorem.replace("culpa et culpa", "culpa");
+        wrapped.set_text(&changed, &FONT);
+        assert_eq!(wrapped.text(), changed);
+        assert_eq!(wrapped.breaks, WrappedText::new(changed, 300, &FONT).breaks);
+
+        // Rewrapping to the same width keeps the lines as they are.
+        let breaks = wrapped.breaks.clone();
+        wrapped.rewrap(Some(300), &FONT);
+        assert_eq!(wrapped.breaks, breaks);
+        wrapped.rewrap(None, &FONT);
+        assert_eq!(wrapped.lines_count(), lorem.lines().count() + 1);
+    }
 }
@@ -0,0 +1,142 @@
+use std::fmt;
+
+/// An error that occurred while laying out an [`Element`](crate::elements::Element) tree.
+///
+/// The `path` of each variant holds the indices of the children that lead from the root
+/// `Element` to the one that failed. An empty path refers to the root itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The `minwidth` of an element is greater than its `maxwidth`.
+    InvalidWidth {
+        path: Vec<usize>,
+        minwidth: u32,
+        maxwidth: u32,
+    },
+    /// The `minheight` of an element is greater than its `maxheight`.
+    InvalidHeight {
+        path: Vec<usize>,
+        minheight: u32,
+        maxheight: u32,
+    },
+    /// The buffer of a [`Content::Custom`](crate::elements::Content::Custom) cannot be divided
+    /// into rows of its `height`.
+    InvalidCustom {
+        path: Vec<usize>,
+        len: usize,
+        height: u32,
+    },
+}
+
+impl LayoutError {
+    /// Prefix the path of this [`LayoutError`] with the `index` of the child it occurred in.
+    pub(crate) fn within(mut self, index: usize) -> Self {
+        match &mut self {
+            Self::InvalidWidth { path, .. }
+            | Self::InvalidHeight { path, .. }
+            | Self::InvalidCustom { path, .. } => path.insert(0, index),
+        }
+        self
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWidth {
+                path,
+                minwidth,
+                maxwidth,
+            } => write!(
+                f,
+                "element {path:?}: minwidth ({minwidth}) cannot be greater than maxwidth \
+                ({maxwidth})"
+            ),
+            Self::InvalidHeight {
+                path,
+                minheight,
+                maxheight,
+            } => write!(
+                f,
+                "element {path:?}: minheight ({minheight}) cannot be greater than maxheight \
+                ({maxheight})"
+            ),
+            Self::InvalidCustom { path, len, height } => write!(
+                f,
+                "element {path:?}: custom buffer of {len} pixels cannot be split into rows for a \
+                height of {height}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// An error that occurred while drawing a [`Panel`](crate::Panel).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// The `scroll` of an element lies beyond the end of its content.
+    ///
+    /// The `path` holds the indices of the children that lead from the root
+    /// [`Element`](crate::elements::Element) to the one that failed.
+    ScrollOutOfRange {
+        path: Vec<usize>,
+        scroll: u32,
+        content_height: u32,
+    },
+    /// The `scroll_x` of an element lies beyond the right end of its content.
+    ///
+    /// The `path` holds the indices of the children that lead from the root
+    /// [`Element`](crate::elements::Element) to the one that failed.
+    HorizontalScrollOutOfRange {
+        path: Vec<usize>,
+        scroll: u32,
+        content_width: u32,
+    },
+    /// The pixel buffer that was provided is too small to draw onto.
+    BufferTooSmall { required: usize, len: usize },
+}
+
+impl RenderError {
+    /// Prefix the path of this [`RenderError`] with the `index` of the child it occurred in.
+    pub(crate) fn within(mut self, index: usize) -> Self {
+        match &mut self {
+            Self::ScrollOutOfRange { path, .. } | Self::HorizontalScrollOutOfRange { path, .. } => {
+                path.insert(0, index)
+            }
+            Self::BufferTooSmall { .. } => {}
+        }
+        self
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScrollOutOfRange {
+                path,
+                scroll,
+                content_height,
+            } => write!(
+                f,
+                "element {path:?}: scroll ({scroll}) lies beyond the content height \
+                ({content_height})"
+            ),
+            Self::HorizontalScrollOutOfRange {
+                path,
+                scroll,
+                content_width,
+            } => write!(
+                f,
+                "element {path:?}: horizontal scroll ({scroll}) lies beyond the content width \
+                ({content_width})"
+            ),
+            Self::BufferTooSmall { required, len } => write!(
+                f,
+                "pixel buffer is not large enough: {required} bytes are required, but it is only \
+                {len} bytes long"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
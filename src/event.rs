@@ -0,0 +1,117 @@
+//! Backend-neutral input events.
+//!
+//! Whatever windowing library drives a [`Panel`](crate::Panel), its input can be translated into
+//! [`Event`]s and passed to [`Panel::handle_event`](crate::Panel::handle_event). The `Panel` then
+//! routes them to the handlers of the right [`Element`](crate::elements::Element)s.
+
+/// A key on the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A key that produces a character, such as `a` or `/`.
+    ///
+    /// Note that typed text is delivered separately through [`Event::Text`], so this is mostly
+    /// useful for shortcuts.
+    Char(char),
+    Enter,
+    Tab,
+    Space,
+    Backspace,
+    Delete,
+    Escape,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+/// The modifier keys that were held down while an [`Event`] occurred.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+/// A button on a pointing device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+/// An input event.
+///
+/// Positions are expressed in pixels. When an `Event` is passed to a [`Panel`](crate::Panel),
+/// positions are relative to the top left corner of the `Panel`. By the time the `Event` reaches
+/// the handler of an [`Element`](crate::elements::Element), its position has been translated to
+/// be relative to the top left corner of that `Element`, including its padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A key was pressed, or is being held down and repeats.
+    KeyPress { key: Key, modifiers: Modifiers },
+    /// A key was released.
+    KeyRelease { key: Key, modifiers: Modifiers },
+    /// A character was typed.
+    Text(char),
+    /// The pointer moved to a new position.
+    PointerMove { x: u32, y: u32 },
+    /// A pointer button was pressed.
+    PointerPress { x: u32, y: u32, button: Button },
+    /// A pointer button was released.
+    PointerRelease { x: u32, y: u32, button: Button },
+    /// The scroll wheel was turned while the pointer was at a position.
+    ///
+    /// Positive deltas scroll towards the right and down, in pixels.
+    Scroll {
+        x: u32,
+        y: u32,
+        delta_x: i32,
+        delta_y: i32,
+    },
+    /// The [`Panel`](crate::Panel) was resized.
+    Resize { width: u32, height: u32 },
+}
+
+impl Event {
+    /// Returns the position of the pointer for pointer events, and [`None`] otherwise.
+    pub fn position(&self) -> Option<(u32, u32)> {
+        match *self {
+            Self::PointerMove { x, y }
+            | Self::PointerPress { x, y, .. }
+            | Self::PointerRelease { x, y, .. }
+            | Self::Scroll { x, y, .. } => Some((x, y)),
+            Self::KeyPress { .. }
+            | Self::KeyRelease { .. }
+            | Self::Text(_)
+            | Self::Resize { .. } => None,
+        }
+    }
+
+    /// Returns a copy of this [`Event`] with its position moved by `-dx` and `-dy`.
+    ///
+    /// Positions that would become negative are clamped to zero. Events without a position are
+    /// returned unchanged.
+    pub(crate) fn translated(mut self, dx: i64, dy: i64) -> Self {
+        match &mut self {
+            Self::PointerMove { x, y }
+            | Self::PointerPress { x, y, .. }
+            | Self::PointerRelease { x, y, .. }
+            | Self::Scroll { x, y, .. } => {
+                *x = (*x as i64 - dx).clamp(0, u32::MAX as i64) as u32;
+                *y = (*y as i64 - dy).clamp(0, u32::MAX as i64) as u32;
+            }
+            Self::KeyPress { .. }
+            | Self::KeyRelease { .. }
+            | Self::Text(_)
+            | Self::Resize { .. } => {}
+        }
+        self
+    }
+}
@@ -0,0 +1,248 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::{Block, Pixel, PIXEL_SIZE};
+
+/// An owned RGBA image, as produced by [`Panel::render`](crate::Panel::render).
+///
+/// This is useful for rendering a [`Panel`](crate::Panel) without any window or gpu in sight, for
+/// instance to produce screenshots of an interface in CI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Row-major pixel buffer of `width * height` [`Pixel`]s.
+    pub buf: Vec<Pixel>,
+}
+
+impl Image {
+    /// Returns the [`Pixel`] at the specified coordinates, if it lies within the [`Image`].
+    pub fn get(&self, x: u32, y: u32) -> Option<Pixel> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.buf.get((y * self.width + x) as usize).copied()
+    }
+
+    /// Returns the pixels of this [`Image`] as a flat buffer of RGBA bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.buf.iter().flatten().copied().collect()
+    }
+
+    /// Encode this [`Image`] as a binary PPM (`P6`) and write it to `w`.
+    ///
+    /// The PPM format has no notion of transparency, so the alpha channel is dropped.
+    pub fn write_ppm(&self, mut w: impl Write) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let rgb: Vec<u8> = self
+            .buf
+            .iter()
+            .flat_map(|&[r, g, b, _]| [r, g, b])
+            .collect();
+        w.write_all(&rgb)
+    }
+
+    /// Encode this [`Image`] as an RGBA PNG and write it to `w`.
+    ///
+    /// The image data is stored without compression. That makes for large files, but it spares
+    /// us from pulling in a deflate implementation just for screenshots.
+    pub fn write_png(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend(self.width.to_be_bytes());
+        header.extend(self.height.to_be_bytes());
+        // Bit depth 8, color type 6 (RGBA), default compression, filtering, and no interlacing.
+        header.extend([8, 6, 0, 0, 0]);
+        write_png_chunk(&mut w, b"IHDR", &header)?;
+
+        // Every scanline is prefixed by its filter type, which is always 0 (none) for us.
+        let stride = self.width as usize * PIXEL_SIZE;
+        let mut raw = Vec::with_capacity((stride + 1) * self.height as usize);
+        if stride > 0 {
+            for row in self.buf.chunks_exact(self.width as usize) {
+                raw.push(0);
+                raw.extend(row.iter().flatten());
+            }
+        } else {
+            raw.resize(self.height as usize, 0);
+        }
+        write_png_chunk(&mut w, b"IDAT", &zlib_stored(&raw))?;
+
+        write_png_chunk(&mut w, b"IEND", &[])
+    }
+
+    /// Write this [`Image`] to a PPM file at `path`.
+    ///
+    /// See [`Image::write_ppm`].
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_ppm(io::BufWriter::new(file))
+    }
+
+    /// Write this [`Image`] to a PNG file at `path`.
+    ///
+    /// See [`Image::write_png`].
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_png(io::BufWriter::new(file))
+    }
+}
+
+impl From<Block> for Image {
+    fn from(block: Block) -> Self {
+        Self {
+            width: block.width,
+            height: block.height,
+            buf: block.buf,
+        }
+    }
+}
+
+fn write_png_chunk(w: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    w.write_all(&crc.to_be_bytes())
+}
+
+/// Wrap `data` in a zlib stream made up of uncompressed (stored) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_STORED: usize = u16::MAX as usize;
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_STORED * 5 + 11);
+    out.extend([0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_STORED).peekable();
+    if blocks.peek().is_none() {
+        // An empty stream still needs a single, final, empty block.
+        out.extend([0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest number of bytes that can be summed before `b` may overflow.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+fn crc32<'d>(data: impl IntoIterator<Item = &'d u8>) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256 {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 == 1 {
+                    0xedb88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    !data.into_iter().fold(!0u32, |c, &byte| {
+        TABLE[((c ^ byte as u32) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{Alignment, Content, Element};
+    use crate::testing::font;
+    use crate::Panel;
+
+    const RED: Pixel = [0xff, 0x00, 0x00, 0xff];
+    const BLUE: Pixel = [0x00, 0x00, 0xff, 0x80];
+
+    fn checkers() -> Image {
+        Image {
+            width: 2,
+            height: 2,
+            buf: vec![RED, BLUE, BLUE, RED],
+        }
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        checkers().write_ppm(&mut out).unwrap();
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([0xff, 0, 0, 0, 0, 0xff, 0, 0, 0xff, 0xff, 0, 0]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn png() {
+        let mut out = Vec::new();
+        checkers().write_png(&mut out).unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+        // IHDR: length, kind, width, height, and then the format bytes.
+        assert_eq!(&out[8..16], b"\x00\x00\x00\x0dIHDR");
+        assert_eq!(&out[16..24], &[0, 0, 0, 2, 0, 0, 0, 2]);
+        assert_eq!(&out[24..29], &[8, 6, 0, 0, 0]);
+        assert_eq!(
+            &out[out.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"
+        );
+
+        // The IDAT payload is a stored zlib stream, so the raw scanlines are in there verbatim.
+        let idat_start = 8 + 25 + 8;
+        let idat_len = u32::from_be_bytes(out[33..37].try_into().unwrap()) as usize;
+        let zlib = &out[idat_start..idat_start + idat_len];
+        let raw = &zlib[7..zlib.len() - 4];
+        let mut expected = vec![0];
+        expected.extend(RED.iter().chain(&BLUE));
+        expected.push(0);
+        expected.extend(BLUE.iter().chain(&RED));
+        assert_eq!(raw, expected);
+    }
+
+    #[test]
+    fn render_panel() {
+        let font = font();
+        let element = Element::still(font, Content::Text("hi".to_string(), Alignment::Left))
+            .with_padding_left(3)
+            .with_background(RED);
+        let panel = Panel::new(element, [0, 0, 0, 0xff], [0xff; 4], ()).unwrap();
+        let image = panel.render().unwrap();
+        assert_eq!((image.width, image.height), (panel.width, panel.height));
+        assert_eq!(image.buf.len(), (image.width * image.height) as usize);
+        assert_eq!(image.get(0, 0), Some(RED));
+        assert_eq!(image.get(image.width, 0), None);
+    }
+}
@@ -1,21 +1,28 @@
 #![feature(iter_intersperse)]
 
-use block::{Block, DrawBlock};
-use elements::{Dimensions, Element};
+pub use blend::BlendMode;
+use block::{Block, Canvas};
+pub use elements::Rect;
+use elements::{simplify_damage, Constraints, Dimensions, Element, Hit};
+pub use error::{LayoutError, RenderError};
+pub use event::Event;
+use event::Key;
+pub use image::Image;
 
+mod blend;
 mod block;
 pub mod elements;
+mod error;
+pub mod event;
+pub mod image;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 /// The number of bytes per [`Pixel`].
 pub const PIXEL_SIZE: usize = 4;
 /// A pixel in the form `[r, g, b, a]`.
 pub type Pixel = [u8; PIXEL_SIZE];
 
-/// An iterator over rows of [`Pixel`]s.
-type Rows<'b> = std::slice::ChunksExact<'b, Pixel>;
-/// An iterator over mutable rows of [`Pixel`]s.
-type RowsMut<'b> = std::slice::ChunksExactMut<'b, Pixel>;
-
 /// Representation of the window and associated data of type `D`.
 pub struct Panel<D> {
     pub width: u32,
@@ -28,21 +35,53 @@ pub struct Panel<D> {
 
     data: D,
     pub elements: Element<D>,
+    /// The path to the [`Element`] that holds the keyboard focus, if any.
+    focus: Option<Vec<usize>>,
+    /// The path to the [`Element`] that handled the last pointer press, for as long as the
+    /// pointer has not been released.
+    grab: Option<Vec<usize>>,
+    /// What the last [`Panel::draw_damaged`] left in the pixel buffer, if it succeeded.
+    frame: Option<Frame>,
+    /// The damaged areas of the last [`Panel::draw_damaged`], kept to reuse the allocation.
+    damage: Vec<Rect>,
+}
+
+/// A description of a drawn frame, such that only the parts that changed since need to be redrawn.
+struct Frame {
+    width: u32,
+    height: u32,
+    /// The background of the [`Panel`] when the frame was drawn.
+    background: Pixel,
+    /// The visible area of the root [`Element`] when the frame was drawn.
+    root: Rect,
 }
 
 impl<D> Panel<D> {
     /// Creates a new [`Panel<D>`].
-    pub fn new(mut elements: Element<D>, foreground: Pixel, background: Pixel, data: D) -> Self {
-        elements.bake_size(None); // We calculate the sizes in order to give the first estimate.
+    ///
+    /// # Errors
+    ///
+    /// If the `elements` cannot be laid out, a [`LayoutError`] is returned.
+    pub fn new(
+        mut elements: Element<D>,
+        foreground: Pixel,
+        background: Pixel,
+        data: D,
+    ) -> Result<Self, LayoutError> {
+        elements.layout(Constraints::default())?; // We calculate the sizes in order to give the first estimate.
         let Dimensions { width, height } = elements.overall_size();
-        Self {
+        Ok(Self {
             width,
             height,
             foreground,
             background,
             data,
             elements,
-        }
+            focus: None,
+            grab: None,
+            frame: None,
+            damage: Vec::new(),
+        })
     }
 
     /// Returns a mutable reference to the data of this [`Panel<D>`].
@@ -50,29 +89,340 @@ impl<D> Panel<D> {
         &mut self.data
     }
 
+    /// Returns the first [`Element`] with the identifier `id`, see [`Element::get`].
+    pub fn get(&self, id: &str) -> Option<&Element<D>> {
+        self.elements.get(id)
+    }
+
+    /// Returns the first [`Element`] with the identifier `id` mutably, see [`Element::get_mut`].
+    ///
+    /// Changes that affect the layout take effect at the next [`Panel::update`].
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Element<D>> {
+        self.elements.get_mut(id)
+    }
+
     /// Update all elements in this [`Panel<D>`] with the internal `data`.
-    pub fn update(&mut self) {
+    ///
+    /// # Errors
+    ///
+    /// If an `update` function leaves the elements in a state that cannot be laid out, a
+    /// [`LayoutError`] is returned that describes which element is at fault. The `Panel` can still
+    /// be drawn afterwards, but the sizes of the elements may be stale.
+    ///
+    /// The root element is laid out within the size of the `Panel`, see [`Panel::resize`]. A
+    /// [`VirtualList`](elements::VirtualList) only builds the rows in view, which are only known
+    /// once it has been laid out, so the rows that came into view are built and laid out after.
+    pub fn update(&mut self) -> Result<(), LayoutError> {
         self.elements.update(&self.data);
-        self.elements.bake_size(Some(self.width));
+        self.validate_focus();
+        let constraints = Constraints::new(Some(self.width), Some(self.height));
+        self.elements.layout(constraints)?;
+        if self.elements.read_visible_rows(&self.data) {
+            self.validate_focus();
+            self.elements.layout(constraints)?;
+        }
+        Ok(())
     }
 
     /// Draw the [`Panel<D>`] onto a pixel buffer.
     ///
     /// The pixel buffer is provided as a mutable slice of bytes. It is assumed that this buffer
-    /// uses the same pixel representation as [`Block`], which is 32-bit RGBA pixels.
+    /// uses the same pixel representation as [`Block`], which is 32-bit RGBA pixels, and that its
+    /// rows are [`Panel::width`] pixels wide. The buffer must hold at least
+    /// `width * height * PIXEL_SIZE` bytes.
+    ///
+    /// The elements are painted straight into the buffer, without allocating anything.
+    ///
+    /// See also: [`Pixel`], [`Panel::render`], [`Panel::draw_damaged`].
+    ///
+    /// # Errors
+    ///
+    /// A [`RenderError`] is returned when the `pixels` buffer is too small, or when an element
+    /// cannot be drawn.
+    pub fn draw(&self, pixels: &mut [u8]) -> Result<(), RenderError> {
+        self.paint(&mut self.canvas(pixels)?)
+    }
+
+    /// Render the [`Panel<D>`] into an owned [`Image`].
+    ///
+    /// This does not need any pixel buffer from a window, which makes it useful for headless
+    /// rendering. The resulting `Image` has the dimensions of the `Panel`.
+    ///
+    /// # Errors
     ///
-    /// See also: [`Pixel`].
-    pub fn draw(&self, pixels: &mut [u8]) {
+    /// A [`RenderError`] is returned when an element cannot be drawn.
+    pub fn render(&self) -> Result<Image, RenderError> {
         let mut block = Block::new(self.width, self.height, self.background);
+        self.paint(&mut block.canvas())?;
+        Ok(block.into())
+    }
+
+    /// Draw the parts of the [`Panel<D>`] that changed since the previous call onto a pixel buffer.
+    ///
+    /// Every [`Element`] remembers what it looked like when it was last drawn. Only the areas of
+    /// the `Element`s whose content, style, size or position changed since then are redrawn onto
+    /// `pixels`. The rest of `pixels` is left untouched, so it must still hold the previous frame,
+    /// as the buffers of most windowing backends do. The first call, and any call after the
+    /// `Panel` was resized or its background was changed, redraws everything.
+    ///
+    /// Returns the damaged [`Rect`]s, which backends can use to present partial updates. When
+    /// nothing changed, no `Rect`s are returned. Like [`Panel::draw`], this does not allocate
+    /// once the `Panel` has settled.
+    ///
+    /// # Errors
+    ///
+    /// A [`RenderError`] is returned when the `pixels` buffer is too small, or when an element
+    /// cannot be drawn. After an error, the next call redraws everything.
+    pub fn draw_damaged(&mut self, pixels: &mut [u8]) -> Result<&[Rect], RenderError> {
+        // Check the buffer before the elements are marked as drawn.
+        self.canvas(pixels)?;
+        let full = Rect::new(0, 0, self.width, self.height);
+        let Dimensions { width, height } = self.elements.overall_size();
+        let root = full.intersection(&Rect::new(0, 0, width, height));
+        let mut damage = std::mem::take(&mut self.damage);
+        damage.clear();
+        // The frame is only put back once it is drawn successfully, so that a failed draw is
+        // followed by a full one.
+        match self.frame.take() {
+            Some(frame)
+                if (frame.width, frame.height) == (self.width, self.height)
+                    && frame.background == self.background =>
+            {
+                if frame.root != root {
+                    damage.extend([frame.root, root]);
+                }
+            }
+            _ => damage.push(full),
+        }
+        self.elements.collect_damage(0, 0, full, &mut damage);
+        // Elements that were drawn before a resize may have covered more than the current frame.
+        for rect in &mut damage {
+            *rect = rect.intersection(&full);
+        }
+        simplify_damage(&mut damage);
+        for &rect in &damage {
+            self.paint(&mut self.canvas(pixels)?.clipped(rect))?;
+        }
 
-        // Draw onto our block.
-        block.paint(&self.elements.block(), 0, 0);
+        self.frame = Some(Frame {
+            width: self.width,
+            height: self.height,
+            background: self.background,
+            root,
+        });
+        self.damage = damage;
+        Ok(&self.damage)
+    }
+
+    /// Returns a [`Canvas`] that covers the [`Panel<D>`] on the `pixels` buffer.
+    fn canvas<'p>(&self, pixels: &'p mut [u8]) -> Result<Canvas<'p>, RenderError> {
+        let required = self.width as usize * self.height as usize * PIXEL_SIZE;
+        if pixels.len() < required {
+            return Err(RenderError::BufferTooSmall {
+                required,
+                len: pixels.len(),
+            });
+        }
+        let (pixels, _) = pixels.as_chunks_mut::<PIXEL_SIZE>();
+        Ok(Canvas::new(pixels, self.width, self.height))
+    }
 
-        // Draw the block onto the pixels.
-        block.draw_onto_pixels(pixels);
+    /// Paint the background and the elements onto a `canvas` that covers the [`Panel<D>`].
+    ///
+    /// The background replaces whatever was on the `canvas` before, so that a translucent
+    /// background is not blended with the previous frame.
+    fn paint(&self, canvas: &mut Canvas) -> Result<(), RenderError> {
+        canvas.set_blend_mode(BlendMode::Replace);
+        canvas.fill(self.background);
+        let Dimensions { width, height } = self.elements.overall_size();
+        self.elements.paint(&mut canvas.view(0, 0, width, height))
+    }
+
+    /// Find the [`Element`] that lies under the position `x`, `y` of the [`Panel<D>`].
+    ///
+    /// The returned [`Hit`] holds the path of child indices from the root `Element` to the hit
+    /// `Element`, and the position relative to that `Element`'s top left corner. Returns [`None`]
+    /// if the position does not lie on any `Element`.
+    ///
+    /// See also: [`Element::hit_test`].
+    pub fn hit_test(&self, x: u32, y: u32) -> Option<Hit> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.elements.hit_test(x, y)
+    }
+
+    /// Handle an input [`Event`].
+    ///
+    /// Events with a position, such as pointer presses, are dispatched to the [`Element`] under
+    /// that position. Keyboard and text events are dispatched to the focused `Element`, or to the
+    /// root `Element` if nothing is focused. Other events are dispatched to the root `Element`. A
+    /// [`Event::Resize`] resizes the `Panel` before it is dispatched.
+    ///
+    /// The handler of the target `Element` receives the `Event` first. If it does not handle the
+    /// `Event`, it is passed on to the handlers of its ancestors, one by one. The handlers may
+    /// mutate the `data`, so [`Panel::update`] should be called before the next draw.
+    ///
+    /// A pointer press moves the focus to the deepest focusable `Element` under the pointer, or
+    /// drops it if there is none. A Tab key press that is not handled by any `Element` moves the
+    /// focus to the next focusable `Element`, or to the previous one when shift is held.
+    ///
+    /// When a pointer press is handled, the pointer is grabbed: the moves and the release that
+    /// follow it go to the same `Element`, even when the pointer leaves it. This is what lets the
+    /// thumb of a [`Scrollbar`](elements::Scrollbar) be dragged.
+    ///
+    /// Returns whether the `Event` was handled by any `Element`, which is a good hint for whether
+    /// a redraw is needed.
+    pub fn handle_event(&mut self, event: Event) -> bool {
+        self.validate_focus();
+        let path = match event {
+            Event::Resize { width, height } => {
+                self.resize(width, height);
+                Vec::new()
+            }
+            Event::KeyPress { .. } | Event::KeyRelease { .. } | Event::Text(_) => {
+                self.focus.clone().unwrap_or_default()
+            }
+            Event::PointerMove { .. } | Event::PointerRelease { .. } if self.grab.is_some() => {
+                let grab = self.grab.clone().unwrap_or_default();
+                if let Event::PointerRelease { .. } = event {
+                    self.grab = None;
+                }
+                grab
+            }
+            _ => match event.position() {
+                Some((x, y)) => match self.hit_test(x, y) {
+                    Some(hit) => hit.path,
+                    None => return false,
+                },
+                None => Vec::new(),
+            },
+        };
+
+        if let Event::PointerPress { .. } = event {
+            let focusable = (0..=path.len())
+                .rev()
+                .map(|len| &path[..len])
+                .find(|path| self.elements.descendant(path).is_some_and(|e| e.focusable));
+            match focusable {
+                Some(focusable) => {
+                    self.focus(focusable);
+                }
+                None => self.blur(),
+            }
+        }
+
+        let handled = self.elements.dispatch(&path, event, &mut self.data);
+        if let Event::PointerPress { .. } = event {
+            self.grab = handled.then_some(path);
+        }
+        match event {
+            Event::KeyPress {
+                key: Key::Tab,
+                modifiers,
+            } if !handled => {
+                if modifiers.shift {
+                    self.focus_previous()
+                } else {
+                    self.focus_next()
+                }
+            }
+            _ => handled,
+        }
+    }
+
+    /// Returns the path to the [`Element`] that holds the keyboard focus, if any.
+    ///
+    /// See [`Element::descendant`].
+    pub fn focused(&self) -> Option<&[usize]> {
+        self.focus.as_deref()
+    }
+
+    /// Move the keyboard focus to the [`Element`] at `path`.
+    ///
+    /// Returns whether the focus was moved. If there is no `Element` at `path`, or it is not
+    /// focusable, the focus is left as it was.
+    pub fn focus(&mut self, path: &[usize]) -> bool {
+        match self.elements.descendant(path) {
+            Some(element) if element.focusable => {}
+            _ => return false,
+        }
+        self.blur();
+        if let Some(element) = self.elements.descendant_mut(path) {
+            element.set_focused(true);
+        }
+        self.focus = Some(path.to_vec());
+        true
+    }
+
+    /// Drop the keyboard focus, such that no [`Element`] is focused.
+    pub fn blur(&mut self) {
+        if let Some(path) = self.focus.take() {
+            if let Some(element) = self.elements.descendant_mut(&path) {
+                element.set_focused(false);
+            }
+        }
+    }
+
+    /// Move the keyboard focus to the next focusable [`Element`] in tree order, wrapping around at
+    /// the end.
+    ///
+    /// Returns whether any `Element` could be focused.
+    pub fn focus_next(&mut self) -> bool {
+        self.move_focus(true)
+    }
+
+    /// Move the keyboard focus to the previous focusable [`Element`] in tree order, wrapping
+    /// around at the start.
+    ///
+    /// Returns whether any `Element` could be focused.
+    pub fn focus_previous(&mut self) -> bool {
+        self.move_focus(false)
+    }
+
+    fn move_focus(&mut self, forward: bool) -> bool {
+        let paths = self.elements.focusable_paths();
+        if paths.is_empty() {
+            return false;
+        }
+        let current = self
+            .focus
+            .as_ref()
+            .and_then(|focus| paths.iter().position(|path| path == focus));
+        let idx = match (current, forward) {
+            (Some(idx), true) => (idx + 1) % paths.len(),
+            (Some(idx), false) => (idx + paths.len() - 1) % paths.len(),
+            (None, true) => 0,
+            (None, false) => paths.len() - 1,
+        };
+        self.focus(&paths[idx])
+    }
+
+    /// Follow the focused [`Element`] if an `update` has moved it to another path, such as a
+    /// reordered child of a [`ListBinding`](elements::ListBinding). Otherwise, drop the keyboard
+    /// focus if an `update` has removed the focused `Element` or made it unfocusable.
+    fn validate_focus(&mut self) {
+        let Some(path) = &self.focus else {
+            return;
+        };
+        let focused = self.elements.descendant(path);
+        if !focused.is_some_and(Element::is_focused) {
+            // Whatever took the place of the focused element was never focused itself, so the
+            // focus is only kept if the element has moved elsewhere.
+            self.focus = self.elements.path_where(Element::is_focused);
+        }
+        let Some(path) = &self.focus else {
+            return;
+        };
+        let focused = self.elements.descendant(path);
+        if !focused.is_some_and(|element| element.focusable) {
+            self.blur();
+        }
     }
 
     /// Resize the [`Panel<D>`].
+    ///
+    /// The elements are laid out within the new size at the next [`Panel::update`].
     pub fn resize(&mut self, width: u32, height: u32) {
         self.width = width;
         self.height = height;
@@ -0,0 +1,252 @@
+//! Golden-image snapshot testing for [`Element`] trees.
+//!
+//! An `Element` is laid out within a given width, rendered through [`render`], and compared to
+//! a reference image `<name>.png` in a directory of snapshots, see [`assert_snapshot_in`]. This
+//! module is only available with the `testing` feature.
+//!
+//! To snapshot the `Element`s of another crate, point it at a directory of that crate:
+//!
+//! ```ignore
+//! let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots");
+//! stammer::testing::assert_snapshot_in(dir, "status", 120, element);
+//! ```
+//!
+//! When the rendered image does not match the reference, a `<name>.new.png` with the actual result
+//! and a `<name>.diff.png` are written next to the reference. The diff shows the reference faded
+//! out, with the pixels that changed painted in bright red.
+//!
+//! To accept new or changed snapshots, run the tests with `STAMMER_BLESS=1` set.
+
+use std::path::Path;
+use std::rc::Rc;
+
+use fleck::Font;
+
+use crate::block::Block;
+use crate::elements::{Constraints, Dimensions, Element};
+use crate::{Image, Pixel, RenderError};
+
+const BLESS_VAR: &str = "STAMMER_BLESS";
+const DIFF_COLOR: Pixel = [0xff, 0x00, 0x00, 0xff];
+
+/// Returns the bundled `cream12.uf2` font, which the snapshots of this crate are rendered with.
+pub fn font() -> Rc<Font> {
+    Rc::new(Font::new(include_bytes!("../cream12.uf2")))
+}
+
+/// Render `element` onto an [`Image`] of its overall size, as it was last laid out.
+pub fn render<D>(element: &Element<D>) -> Result<Image, RenderError> {
+    let Dimensions { width, height } = element.overall_size();
+    let mut block = Block::new(width, height, [0x00; 4]);
+    element.paint(&mut block.canvas())?;
+    Ok(block.into())
+}
+
+/// Lay out `element` within `width`, render it, and compare it to the reference snapshot `name`
+/// in `tests/snapshots` of this crate.
+#[cfg(test)]
+pub(crate) fn assert_snapshot<D>(name: &str, width: u32, element: Element<D>) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    assert_snapshot_in(dir, name, width, element)
+}
+
+/// Lay out `element` within `width`, render it, and compare it to the reference snapshot `name`
+/// in the directory `dir`.
+///
+/// # Panics
+///
+/// If the reference does not exist or the rendered [`Element`] differs from it, this function
+/// panics. If `STAMMER_BLESS` is set, the reference is (re)written instead.
+pub fn assert_snapshot_in<D>(
+    dir: impl AsRef<Path>,
+    name: &str,
+    width: u32,
+    mut element: Element<D>,
+) {
+    if let Err(err) = element.layout(Constraints::default().with_maxwidth(width)) {
+        panic!("snapshot {name:?} could not be laid out: {err}");
+    }
+    let actual = render(&element)
+        .unwrap_or_else(|err| panic!("snapshot {name:?} could not be rendered: {err}"));
+
+    let dir = dir.as_ref();
+    let reference_path = dir.join(format!("{name}.png"));
+    let new_path = dir.join(format!("{name}.new.png"));
+    let diff_path = dir.join(format!("{name}.diff.png"));
+
+    if std::env::var_os(BLESS_VAR).is_some() {
+        std::fs::create_dir_all(dir).unwrap();
+        actual.save_png(&reference_path).unwrap();
+        let _ = std::fs::remove_file(new_path);
+        let _ = std::fs::remove_file(diff_path);
+        return;
+    }
+
+    let Ok(encoded) = std::fs::read(&reference_path) else {
+        std::fs::create_dir_all(dir).unwrap();
+        actual.save_png(&new_path).unwrap();
+        panic!(
+            "snapshot {name:?} has no reference at {reference_path:?}; the rendered result was \
+            written to {new_path:?}. Run with {BLESS_VAR}=1 to accept it."
+        );
+    };
+    let reference = read_png(&encoded)
+        .unwrap_or_else(|err| panic!("could not read reference {reference_path:?}: {err}"));
+
+    if reference == actual {
+        let _ = std::fs::remove_file(new_path);
+        let _ = std::fs::remove_file(diff_path);
+        return;
+    }
+
+    let (diff, changed) = diff(&reference, &actual);
+    actual.save_png(&new_path).unwrap();
+    diff.save_png(&diff_path).unwrap();
+    panic!(
+        "snapshot {name:?} does not match its reference: {changed} pixels differ (reference is \
+        {}x{}, actual is {}x{}). See {diff_path:?} and {new_path:?}. Run with {BLESS_VAR}=1 to \
+        accept the new result.",
+        reference.width, reference.height, actual.width, actual.height
+    );
+}
+
+/// Create an [`Image`] that highlights the pixels that differ between `a` and `b`.
+///
+/// Returns the diff image and the number of pixels that differ. If the sizes differ, the diff
+/// covers the largest extent of the two, and pixels that only exist in one of them count as
+/// changed.
+fn diff(a: &Image, b: &Image) -> (Image, usize) {
+    let width = a.width.max(b.width);
+    let height = a.height.max(b.height);
+    let mut changed = 0;
+    let mut buf = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let px = match (a.get(x, y), b.get(x, y)) {
+                (Some(pa), Some(pb)) if pa == pb => {
+                    // Fade the unchanged pixels so the changes stand out.
+                    let [r, g, b, _] = pa;
+                    let luma = (r as u32 * 3 + g as u32 * 6 + b as u32) / 10;
+                    let faded = 0xc0 + (luma / 4) as u8;
+                    [faded, faded, faded, 0xff]
+                }
+                _ => {
+                    changed += 1;
+                    DIFF_COLOR
+                }
+            };
+            buf.push(px);
+        }
+    }
+    (Image { width, height, buf }, changed)
+}
+
+/// Decode a PNG as written by [`Image::write_png`].
+///
+/// This is by no means a general PNG decoder. It only supports 8-bit RGBA images without
+/// filtering whose data is stored in uncompressed deflate blocks.
+fn read_png(data: &[u8]) -> Result<Image, String> {
+    let data = data
+        .strip_prefix(b"\x89PNG\r\n\x1a\n")
+        .ok_or("not a png file")?;
+
+    let mut width = 0;
+    let mut height = 0;
+    let mut zlib = Vec::new();
+    let mut rest = data;
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + len).ok_or("truncated chunk")?;
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(body[..4].try_into().unwrap());
+                height = u32::from_be_bytes(body[4..8].try_into().unwrap());
+                if body[8..13] != [8, 6, 0, 0, 0] {
+                    return Err("only 8-bit non-interlaced RGBA is supported".to_string());
+                }
+            }
+            b"IDAT" => zlib.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = &rest[12 + len..];
+    }
+
+    // Skip the zlib header, then collect the stored deflate blocks.
+    let mut raw = Vec::new();
+    let mut blocks = zlib.get(2..).ok_or("missing zlib header")?;
+    loop {
+        let (&header, rest) = blocks.split_first().ok_or("truncated deflate stream")?;
+        if header & 0b110 != 0 {
+            return Err("only stored deflate blocks are supported".to_string());
+        }
+        let len = u16::from_le_bytes(rest.get(..2).ok_or("truncated block")?.try_into().unwrap());
+        let block = rest.get(4..4 + len as usize).ok_or("truncated block")?;
+        raw.extend_from_slice(block);
+        blocks = &rest[4 + len as usize..];
+        if header & 1 == 1 {
+            break;
+        }
+    }
+
+    let stride = width as usize * 4 + 1;
+    if raw.len() != stride * height as usize {
+        return Err("image data does not match the dimensions".to_string());
+    }
+    let mut buf = Vec::with_capacity(width as usize * height as usize);
+    for line in raw.chunks_exact(stride) {
+        if line[0] != 0 {
+            return Err("only unfiltered scanlines are supported".to_string());
+        }
+        buf.extend(
+            line[1..]
+                .chunks_exact(4)
+                .map(|px| -> Pixel { px.try_into().unwrap() }),
+        );
+    }
+    Ok(Image { width, height, buf })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_roundtrip() {
+        let image = Image {
+            width: 3,
+            height: 2,
+            buf: vec![
+                [1, 2, 3, 4],
+                [5, 6, 7, 8],
+                [9, 10, 11, 12],
+                [0; 4],
+                [0xff; 4],
+                [7; 4],
+            ],
+        };
+        let mut encoded = Vec::new();
+        image.write_png(&mut encoded).unwrap();
+        assert_eq!(read_png(&encoded), Ok(image));
+    }
+
+    #[test]
+    fn diff_marks_changes() {
+        let a = Image {
+            width: 2,
+            height: 1,
+            buf: vec![[0; 4], [0; 4]],
+        };
+        let b = Image {
+            width: 1,
+            height: 2,
+            buf: vec![[0; 4], [1; 4]],
+        };
+        let (diff, changed) = diff(&a, &b);
+        assert_eq!((diff.width, diff.height), (2, 2));
+        assert_eq!(changed, 3);
+        assert_eq!(diff.get(0, 0), Some([0xc0, 0xc0, 0xc0, 0xff]));
+        assert_eq!(diff.get(1, 0), Some(DIFF_COLOR));
+    }
+}